@@ -3,7 +3,7 @@ use std::io::{Read, Write};
 use aes::cipher::{self, block_padding, generic_array, KeyInit, KeyIvInit};
 
 use crate::{
-    codecs::{Codec, CodecMode, CodecUsage, Options},
+    codecs::{Codec, CodecMode, CodecUsage, Example, OptionSpec, Options},
     utils::{BytesToBytesDecoder, BytesToBytesEncoder, DeathRattle},
 };
 
@@ -82,13 +82,8 @@ impl AesCodec {
 impl CodecUsage for AesCodec {
     fn usage(&self) -> String {
         match self.mode {
-            BlockCipherMode::Cbc => "    -K key
-    -IV iv
-"
-            .to_string(),
-            BlockCipherMode::Ecb => "    -K key
-"
-            .to_string(),
+            BlockCipherMode::Cbc => "    block cipher in cbc mode with pkcs7 padding\n".to_string(),
+            BlockCipherMode::Ecb => "    block cipher in ecb mode with pkcs7 padding\n".to_string(),
         }
     }
 }
@@ -96,7 +91,7 @@ impl CodecUsage for AesCodec {
 impl Codec for AesCodec {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -134,6 +129,61 @@ impl Codec for AesCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        let mut options = vec![OptionSpec::text("K", "key", "key")
+            .long("key")
+            .required()
+            .sensitive()];
+        if let BlockCipherMode::Cbc = self.mode {
+            options.push(
+                OptionSpec::text("IV", "iv", "initialization vector")
+                    .long("iv")
+                    .required(),
+            );
+        }
+        Some(options)
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        Some("libc")
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        match (&self.cipher_type, &self.mode) {
+            (BlockCipherType::Aes, BlockCipherMode::Cbc) => vec![
+                Example::new(
+                    "aes-cbc -K 0123456789abcdef -IV fedcba9876543210 base64",
+                    "hello",
+                    "e+3OsGVfOJQQaXifNkYgkQ==",
+                ),
+                Example::new(
+                    "base64 -d aes-cbc -d -K 0123456789abcdef -IV fedcba9876543210",
+                    "e+3OsGVfOJQQaXifNkYgkQ==",
+                    "hello",
+                ),
+            ],
+            (BlockCipherType::Aes, BlockCipherMode::Ecb) => vec![Example::new(
+                "aes-ecb -K 0123456789abcdef hex",
+                "hello",
+                "674c7ef38e78cabd9cec9c125823a639",
+            )],
+            (BlockCipherType::Sm4, BlockCipherMode::Cbc) => vec![Example::new(
+                "sm4-cbc -K 0123456789abcdef -IV fedcba9876543210 base64",
+                "hello",
+                "+2I9nGnj+QYay2OUKUD3tg==",
+            )],
+            (BlockCipherType::Sm4, BlockCipherMode::Ecb) => vec![Example::new(
+                "sm4-ecb -K 0123456789abcdef hex",
+                "hello",
+                "069efe111066b0ce423a0cb7a67f9936",
+            )],
+        }
+    }
 }
 
 impl Sm4Codec {
@@ -161,7 +211,7 @@ impl CodecUsage for Sm4Codec {
 impl Codec for Sm4Codec {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -172,6 +222,22 @@ impl Codec for Sm4Codec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        self.0.options()
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        Some("libc")
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        self.0.examples()
+    }
 }
 
 impl BlockCipherType {
@@ -263,7 +329,7 @@ where
     N: generic_array::ArrayLength<u8>,
 {
     let n = N::to_usize();
-    debug_assert!(data.len() % n == 0);
+    debug_assert!(data.len().is_multiple_of(n));
 
     #[allow(unsafe_code)]
     unsafe {
@@ -1,4 +1,4 @@
-use crate::codecs::{Codec, CodecUsage, Options};
+use crate::codecs::{Codec, CodecUsage, Example, OptionSpec, Options};
 
 #[derive(Default)]
 pub struct AppendCodecs;
@@ -9,7 +9,7 @@ pub struct NewLineCodecs(AppendCodecs);
 impl Codec for AppendCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -25,18 +25,28 @@ impl Codec for AppendCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::text("A", "string", "string to append")
+            .long("string")
+            .required()])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("append -A ' world'", "hello", "hello world")]
+    }
 }
 
 impl CodecUsage for AppendCodecs {
     fn usage(&self) -> String {
-        "    -A string: pass input to output, and then append `string`\n".to_string()
+        "    pass input to output, and then append `string`\n".to_string()
     }
 }
 
 impl Codec for NewLineCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         _options: &Options,
         output: &mut dyn std::io::Write,
@@ -49,6 +59,18 @@ impl Codec for NewLineCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![])
+    }
+
+    fn expansion(&self, _options: &Options) -> Option<String> {
+        Some("append -A ['\\n' escape -d]".to_string())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("newline", "hi", "hi\n")]
+    }
 }
 
 impl CodecUsage for NewLineCodecs {
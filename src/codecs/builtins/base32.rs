@@ -0,0 +1,259 @@
+use std::str::FromStr;
+
+use crate::{
+    codecs::{Codec, CodecUsage, Example, OptionSpec, Options},
+    utils::{BytesToBytesDecoder, BytesToBytesEncoder, DeathRattle, SkipWhitespace},
+};
+
+#[derive(Default)]
+pub struct Base32Codec;
+
+impl Codec for Base32Codec {
+    fn run_codec(
+        &self,
+        input: &mut (dyn std::io::Read + Send),
+        global_mode: crate::codecs::CodecMode,
+        options: &Options,
+        mut output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let alphabet: Alphabet = options.get_text_str("A")?.unwrap_or("standard").parse()?;
+
+        match global_mode {
+            crate::codecs::CodecMode::Encoding => {
+                let padding = alphabet.is_padded() && !options.get_switch("p");
+                let mut symbols = *alphabet.symbols();
+                if options.get_switch("l") {
+                    symbols.make_ascii_lowercase();
+                }
+
+                let mut writer = BytesToBytesEncoder::new(&mut output, |buf| {
+                    let (groups, remain) = buf.split_at(buf.len() - buf.len() % 5);
+                    Ok((encode(&symbols, groups, padding), remain))
+                });
+                std::io::copy(input, &mut writer)?;
+
+                writer
+                    .finalize()
+                    .death_rattle(|buf| Ok(Some(encode(&symbols, buf, padding))))?;
+                Ok(())
+            }
+            crate::codecs::CodecMode::Decoding => {
+                let table = alphabet.decoding_table();
+                let mut input = SkipWhitespace::new(input);
+
+                let mut reader = BytesToBytesDecoder::new(&mut input, |buf| {
+                    let (bytes, read) = decode(&table, buf, false)?;
+                    Ok((bytes, &buf[read..]))
+                });
+                reader.set_need_finalize(true);
+
+                std::io::copy(&mut reader, output)?;
+
+                reader.finalize().death_rattle((
+                    |buf| decode(&table, buf, true).map(|(bytes, _)| Some(bytes)),
+                    &mut output,
+                ))?;
+                Ok(())
+            }
+        }
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::text("A", "alphabet", "base32 alphabet")
+                .long("alphabet")
+                .one_of(Alphabet::NAMES)
+                .with_default("standard"),
+            OptionSpec::switch("p", "no padding (crockford and z-base-32 are never padded)")
+                .long("no-padding"),
+            OptionSpec::switch("l", "use lowercase letters (only affects encoding)").long("lower"),
+        ])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("base32", "hello", "NBSWY3DP"),
+            Example::new("base32", "hi", "NBUQ===="),
+            Example::new("base32 -p -l", "hi", "nbuq"),
+            Example::new("base32 -A hex", "hi", "D1KG===="),
+            Example::new("base32 -A crockford", "hello", "D1JPRV3F"),
+            Example::new("base32 -A z-base-32", "hello", "pb1sa5dx"),
+            Example::new("base32 -d", "nbswy3dp", "hello"),
+            Example::new("base32 -d -A crockford", "d1jp-rv3f", "hello"),
+        ]
+    }
+}
+
+impl CodecUsage for Base32Codec {
+    fn usage(&self) -> String {
+        "    base32 encode/decode, decoding is case-insensitive and ignores whitespaces
+        (e.g. line breaks)
+    alphabets:
+        standard: RFC 4648 (A-Z 2-7)
+        hex: RFC 4648 extended hex (0-9 A-V), keeps the sort order of the input
+        crockford: Crockford's base32 (0-9 A-Z without I L O U), decodes I and L
+            as 1, O as 0, and ignores hyphens
+        z-base-32: human-oriented base32, lowercase
+"
+        .to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Alphabet {
+    Standard,
+    Hex,
+    Crockford,
+    ZBase32,
+}
+
+impl Alphabet {
+    const NAMES: &'static [&'static str] = &["standard", "hex", "crockford", "z-base-32"];
+
+    fn symbols(self) -> &'static [u8; 32] {
+        match self {
+            Alphabet::Standard => b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567",
+            Alphabet::Hex => b"0123456789ABCDEFGHIJKLMNOPQRSTUV",
+            Alphabet::Crockford => b"0123456789ABCDEFGHJKMNPQRSTVWXYZ",
+            Alphabet::ZBase32 => b"ybndrfg8ejkmcpqxot1uwisza345h769",
+        }
+    }
+
+    /// Whether the last group is padded with `=` to 8 symbols when encoding.
+    fn is_padded(self) -> bool {
+        matches!(self, Alphabet::Standard | Alphabet::Hex)
+    }
+
+    /// The value of each byte as a symbol in either case, or `INVALID`,
+    /// `PADDING` and `IGNORED`.
+    fn decoding_table(self) -> [u8; 256] {
+        let mut table = [INVALID; 256];
+        let mut insert = |symbol: u8, value: u8| {
+            table[symbol.to_ascii_uppercase() as usize] = value;
+            table[symbol.to_ascii_lowercase() as usize] = value;
+        };
+        for (value, &symbol) in self.symbols().iter().enumerate() {
+            insert(symbol, value as u8);
+        }
+        insert(b'=', PADDING);
+        if self == Alphabet::Crockford {
+            insert(b'O', 0);
+            insert(b'I', 1);
+            insert(b'L', 1);
+            insert(b'-', IGNORED);
+        }
+        table
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Alphabet::Standard),
+            "hex" => Ok(Alphabet::Hex),
+            "crockford" => Ok(Alphabet::Crockford),
+            "z-base-32" => Ok(Alphabet::ZBase32),
+            _ => anyhow::bail!(
+                "unknown base32 alphabet: {}, expect one of {}",
+                s,
+                Alphabet::NAMES.join(", ")
+            ),
+        }
+    }
+}
+
+const INVALID: u8 = 0xff;
+const PADDING: u8 = 0xfe;
+const IGNORED: u8 = 0xfd;
+
+/// Encodes `bytes` by groups of 5 bytes as 8 symbols, the last group may be
+/// shorter.
+fn encode(symbols: &[u8; 32], bytes: &[u8], padding: bool) -> Vec<u8> {
+    let mut result = Vec::with_capacity(bytes.len().div_ceil(5) * 8);
+    for group in bytes.chunks(5) {
+        let mut bits = [0u8; 5];
+        bits[..group.len()].copy_from_slice(group);
+        let bits = bits
+            .iter()
+            .fold(0u64, |bits, &byte| bits << 8 | byte as u64);
+
+        let length = (group.len() * 8).div_ceil(5);
+        for i in 0..length {
+            result.push(symbols[(bits >> (35 - i * 5)) as usize & 0x1f]);
+        }
+        if padding {
+            result.resize(result.len() + 8 - length, b'=');
+        }
+    }
+    result
+}
+
+/// Decodes the complete groups of 8 symbols (ignoring padding) of `buf`, and
+/// the last incomplete one if `last`. Returns the decoded bytes and the length
+/// of `buf` read.
+fn decode(table: &[u8; 256], buf: &[u8], last: bool) -> std::io::Result<(Vec<u8>, usize)> {
+    let mut result = Vec::with_capacity(buf.len() / 8 * 5);
+    let mut group = Vec::with_capacity(8);
+    let mut read = 0;
+    let mut padded = false;
+
+    for (i, &byte) in buf.iter().enumerate() {
+        match table[byte as usize] {
+            INVALID => {
+                return Err(invalid_data(format!(
+                    "invalid base32 symbol `{}`",
+                    (byte as char).escape_default()
+                )))
+            }
+            PADDING => padded = true,
+            IGNORED => {}
+            _ if padded => return Err(invalid_data("base32 symbol after padding".to_string())),
+            value => {
+                group.push(value);
+                if group.len() == 8 {
+                    decode_group(&group, &mut result)?;
+                    group.clear();
+                    read = i + 1;
+                }
+            }
+        }
+    }
+
+    if last {
+        decode_group(&group, &mut result)?;
+        read = buf.len();
+    }
+    Ok((result, read))
+}
+
+/// Decodes up to 8 symbol values as up to 5 bytes.
+fn decode_group(values: &[u8], result: &mut Vec<u8>) -> std::io::Result<()> {
+    // 1, 3 and 6 symbols don't encode a whole number of bytes
+    if matches!(values.len(), 1 | 3 | 6) {
+        return Err(invalid_data(format!(
+            "invalid base32 length, {} symbol(s) left",
+            values.len()
+        )));
+    }
+    let length = values.len() * 5 / 8;
+    let bits = values
+        .iter()
+        .fold(0u64, |bits, &value| bits << 5 | value as u64)
+        >> (values.len() * 5 - length * 8);
+    result.extend((0..length).rev().map(|i| (bits >> (i * 8)) as u8));
+    Ok(())
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
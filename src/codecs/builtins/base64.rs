@@ -1,41 +1,51 @@
-use crate::codecs::{Codec, CodecUsage, Options};
+use base64::{
+    alphabet::{self, Alphabet},
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+
+use crate::{
+    codecs::{Codec, CodecUsage, Example, OptionSpec, Options},
+    utils::SkipWhitespace,
+};
 
 #[derive(Default)]
 pub struct Base64Codec;
 
+const ALPHABETS: &[&str] = &["standard", "url", "crypt", "bcrypt", "imap"];
+
 impl Codec for Base64Codec {
     fn run_codec(
         &self,
-        mut input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         options: &Options,
         mut output: &mut dyn std::io::Write,
     ) -> anyhow::Result<()> {
-        let no_padding = options.get_switch("p");
-        #[allow(clippy::collapsible_else_if)]
-            let encoding = &if options.get_switch("u") {
-            if no_padding {
-                base64::engine::general_purpose::URL_SAFE_NO_PAD
+        let no_padding = options.get_switch("np") || options.get_switch("p");
+        let config = GeneralPurposeConfig::new()
+            .with_encode_padding(!no_padding)
+            .with_decode_padding_mode(if no_padding {
+                DecodePaddingMode::Indifferent
             } else {
-                base64::engine::general_purpose::URL_SAFE
-            }
-        } else {
-            if no_padding {
-                base64::engine::general_purpose::STANDARD_NO_PAD
-            } else {
-                base64::engine::general_purpose::STANDARD
-            }
-        };
-        #[warn(clippy::collapsible_else_if)]
+                DecodePaddingMode::RequireCanonical
+            });
+        let engine = GeneralPurpose::new(&get_alphabet(options)?, config);
 
         match global_mode {
             crate::codecs::CodecMode::Encoding => {
-                let mut encoder = base64::write::EncoderWriter::new(&mut output, encoding);
-                std::io::copy(input, &mut encoder)?;
+                let columns = options.get_text::<usize>("w")?.unwrap_or(0);
+                let mut wrapper = LineWrapper::new(&mut output, columns);
+                {
+                    let mut encoder = base64::write::EncoderWriter::new(&mut wrapper, &engine);
+                    std::io::copy(input, &mut encoder)?;
+                    encoder.finish()?;
+                }
+                wrapper.finish()?;
                 Ok(())
             }
             crate::codecs::CodecMode::Decoding => {
-                let mut decoder = base64::read::DecoderReader::new(&mut input, encoding);
+                let mut input = SkipWhitespace::new(input);
+                let mut decoder = base64::read::DecoderReader::new(&mut input, &engine);
                 std::io::copy(&mut decoder, output)?;
                 Ok(())
             }
@@ -45,10 +55,138 @@ impl Codec for Base64Codec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::switch("u", "use url base64 instead (= -A url)").long("url"),
+            OptionSpec::text(
+                "A",
+                "alphabet",
+                "alphabet, one of standard, url, crypt, bcrypt, imap, or any 64 characters",
+            )
+            .long("alphabet")
+            .with_default("standard"),
+            OptionSpec::switch(
+                "np",
+                "no padding when encoding, accept missing padding when decoding",
+            )
+            .long("no-padding"),
+            OptionSpec::switch("p", "same as -np"),
+            OptionSpec::text(
+                "w",
+                "cols",
+                "wrap encoded lines after `cols` characters and end with a newline, 0 to \
+                 disable (e.g. 76 for MIME, 64 for PEM)",
+            )
+            .long("wrap")
+            .int_at_least(0)
+            .with_default("0"),
+        ])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("base64", "hello?", "aGVsbG8/"),
+            Example::new("base64 -u -np", "hello?", "aGVsbG8_"),
+            Example::new("base64 -w 4", "hello?", "aGVs\nbG8/\n"),
+            Example::new("base64 -A bcrypt -np", "hello?", "YETqZE69"),
+            Example::new("base64 -d", "aGVsbG8/", "hello?"),
+            Example::new("base64 -d", "aGVs\r\nbG8/\r\n", "hello?"),
+            Example::new("base64 -d -np", "aGk", "hi"),
+        ]
+    }
 }
 
 impl CodecUsage for Base64Codec {
     fn usage(&self) -> String {
-        "    -u: use url base64 instead\n".to_string()
+        "    base64 encode/decode, decoding ignores whitespaces (e.g. line breaks)
+    alphabets:
+        standard: A-Z a-z 0-9 + /
+        url: A-Z a-z 0-9 - _
+        crypt: . / 0-9 A-Z a-z
+        bcrypt: . / A-Z a-z 0-9
+        imap: A-Z a-z 0-9 + , (IMAP modified UTF-7)
+"
+        .to_string()
+    }
+}
+
+fn get_alphabet(options: &Options) -> anyhow::Result<Alphabet> {
+    let name = options.get_text_str("A")?;
+    if options.get_switch("u") {
+        if name.is_some() {
+            anyhow::bail!("base64: -u and -A cannot be used together");
+        }
+        return Ok(alphabet::URL_SAFE);
+    }
+
+    match name.unwrap_or("standard") {
+        "standard" => Ok(alphabet::STANDARD),
+        "url" => Ok(alphabet::URL_SAFE),
+        "crypt" => Ok(alphabet::CRYPT),
+        "bcrypt" => Ok(alphabet::BCRYPT),
+        "imap" => Ok(alphabet::IMAP_MUTF7),
+        custom => Alphabet::new(custom).map_err(|err| {
+            anyhow::anyhow!(
+                "invalid base64 alphabet `{}`: {}, expect one of {} or 64 characters",
+                custom,
+                err,
+                ALPHABETS.join(", ")
+            )
+        }),
+    }
+}
+
+/// Writer inserting a newline every `columns` bytes, and after the last line
+/// in [`finish`](LineWrapper::finish). Does nothing if `columns` is 0.
+struct LineWrapper<'w> {
+    writer: &'w mut dyn std::io::Write,
+    columns: usize,
+    column: usize,
+}
+
+impl<'w> LineWrapper<'w> {
+    fn new(writer: &'w mut dyn std::io::Write, columns: usize) -> Self {
+        LineWrapper {
+            writer,
+            columns,
+            column: 0,
+        }
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        if self.columns > 0 && self.column > 0 {
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::io::Write for LineWrapper<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.columns == 0 {
+            return self.writer.write(buf);
+        }
+
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.column == self.columns {
+                self.writer.write_all(b"\n")?;
+                self.column = 0;
+            }
+            let n = rest.len().min(self.columns - self.column);
+            self.writer.write_all(&rest[..n])?;
+            self.column += n;
+            rest = &rest[n..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
     }
 }
@@ -0,0 +1,322 @@
+use std::str::FromStr;
+
+use crate::{
+    codecs::{Codec, CodecUsage, Example, OptionSpec, Options},
+    utils::{BytesToBytesDecoder, BytesToBytesEncoder, DeathRattle},
+};
+
+#[derive(Default)]
+pub struct Base85Codec;
+
+impl Codec for Base85Codec {
+    fn run_codec(
+        &self,
+        mut input: &mut (dyn std::io::Read + Send),
+        global_mode: crate::codecs::CodecMode,
+        options: &Options,
+        mut output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let variant: Variant = options.get_text_str("V")?.unwrap_or("ascii85").parse()?;
+
+        match global_mode {
+            crate::codecs::CodecMode::Encoding => {
+                let framed = variant == Variant::Ascii85 && !options.get_switch("u");
+                if framed {
+                    output.write_all(FRAME_START)?;
+                }
+
+                let mut writer = BytesToBytesEncoder::new(&mut output, |buf| {
+                    let (groups, remain) = buf.split_at(buf.len() - buf.len() % 4);
+                    Ok((variant.encode(groups), remain))
+                });
+                std::io::copy(input, &mut writer)?;
+
+                writer.finalize().death_rattle(|buf| {
+                    if variant == Variant::Z85 {
+                        return Err(invalid_data(format!(
+                            "z85 input length must be a multiple of 4, {} byte(s) left",
+                            buf.len()
+                        )));
+                    }
+                    Ok(Some(variant.encode(buf)))
+                })?;
+
+                if framed {
+                    output.write_all(FRAME_END)?;
+                }
+                Ok(())
+            }
+            crate::codecs::CodecMode::Decoding => {
+                let mut decoder = Decoder::new(variant);
+
+                let mut reader = BytesToBytesDecoder::new(&mut input, |buf| {
+                    let (bytes, read) = decoder.decode(buf, false)?;
+                    Ok((bytes, &buf[read..]))
+                });
+                reader.set_need_finalize(true);
+
+                std::io::copy(&mut reader, output)?;
+
+                reader.finalize().death_rattle((
+                    |buf| decoder.decode(buf, true).map(|(bytes, _)| Some(bytes)),
+                    &mut output,
+                ))?;
+                Ok(())
+            }
+        }
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::text("V", "variant", "base85 variant")
+                .long("variant")
+                .one_of(Variant::NAMES)
+                .with_default("ascii85"),
+            OptionSpec::switch(
+                "u",
+                "don't write the <~ ~> delimiters (ascii85 only, only affects encoding)",
+            )
+            .long("unframed"),
+        ])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("base85", "hello", "<~BOu!rDZ~>"),
+            Example::new("hex -d base85 -u", "0000000001", "z!<"),
+            Example::new("base85 -d", "<~BOu!r\nDZ~>", "hello"),
+            Example::new("hex -d base85 -V z85", "864fd26fb559f75b", "HelloWorld"),
+            Example::new("base85 -V rfc1924", "hello", "Xk~0{Zv"),
+            Example::new("base85 -d -V rfc1924", "Xk~0{Zv", "hello"),
+        ]
+    }
+}
+
+impl CodecUsage for Base85Codec {
+    fn usage(&self) -> String {
+        "    base85 encode/decode, 4 bytes as 5 characters
+    variants:
+        ascii85: Adobe Ascii85 (as in PDF and PostScript), framed by <~ ~>, with `z`
+            for 4 zero bytes. decoding ignores whitespaces, and the delimiters
+            if any
+        z85: ZeroMQ Z85, the input length must be a multiple of 4 (5 when decoding)
+        rfc1924: the RFC 1924 character set, as in git binary patches and python
+            base64.b85encode
+"
+        .to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variant {
+    Ascii85,
+    Z85,
+    Rfc1924,
+}
+
+impl Variant {
+    const NAMES: &'static [&'static str] = &["ascii85", "z85", "rfc1924"];
+
+    fn symbols(self) -> &'static [u8; 85] {
+        match self {
+            Variant::Ascii85 => b"!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstu",
+            Variant::Z85 => b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#",
+            Variant::Rfc1924 => b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~",
+        }
+    }
+
+    /// Encodes `bytes` by groups of 4 bytes as 5 symbols, the last group may be
+    /// shorter and is written as one symbol more than its length.
+    fn encode(self, bytes: &[u8]) -> Vec<u8> {
+        let symbols = self.symbols();
+        let mut result = Vec::with_capacity(bytes.len().div_ceil(4) * 5);
+        for group in bytes.chunks(4) {
+            if self == Variant::Ascii85 && group == [0; 4] {
+                result.push(ZEROS);
+                continue;
+            }
+
+            let mut padded = [0u8; 4];
+            padded[..group.len()].copy_from_slice(group);
+            let mut value = u32::from_be_bytes(padded);
+            let mut digits = [0u8; 5];
+            for digit in digits.iter_mut().rev() {
+                *digit = symbols[(value % 85) as usize];
+                value /= 85;
+            }
+            result.extend_from_slice(&digits[..group.len() + 1]);
+        }
+        result
+    }
+}
+
+impl FromStr for Variant {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii85" => Ok(Variant::Ascii85),
+            "z85" => Ok(Variant::Z85),
+            "rfc1924" => Ok(Variant::Rfc1924),
+            _ => anyhow::bail!(
+                "unknown base85 variant: {}, expect one of {}",
+                s,
+                Variant::NAMES.join(", ")
+            ),
+        }
+    }
+}
+
+const FRAME_START: &[u8] = b"<~";
+const FRAME_END: &[u8] = b"~>";
+/// Ascii85 shortcut for a group of 4 zero bytes.
+const ZEROS: u8 = b'z';
+const INVALID: u8 = 0xff;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DecoderState {
+    /// Before the data, where `<~` may start the ascii85 frame.
+    Start,
+    Data,
+    /// After the `~>` ending the ascii85 frame.
+    End,
+}
+
+struct Decoder {
+    variant: Variant,
+    /// Value of each byte as a symbol, or `INVALID`.
+    table: [u8; 256],
+    state: DecoderState,
+}
+
+impl Decoder {
+    fn new(variant: Variant) -> Self {
+        let mut table = [INVALID; 256];
+        for (value, &symbol) in variant.symbols().iter().enumerate() {
+            table[symbol as usize] = value as u8;
+        }
+        Decoder {
+            variant,
+            table,
+            state: DecoderState::Start,
+        }
+    }
+
+    /// Decodes the complete groups of 5 symbols of `buf`, and the last
+    /// incomplete one if `last`. Returns the decoded bytes and the length of
+    /// `buf` read.
+    fn decode(&mut self, buf: &[u8], last: bool) -> std::io::Result<(Vec<u8>, usize)> {
+        let ascii85 = self.variant == Variant::Ascii85;
+        let mut result = Vec::with_capacity(buf.len() / 5 * 4);
+        let mut group = Vec::with_capacity(5);
+        let mut read = 0;
+        let mut i = 0;
+
+        while i < buf.len() {
+            let byte = buf[i];
+            match self.state {
+                _ if ascii85 && byte.is_ascii_whitespace() => {}
+                DecoderState::Start => {
+                    self.state = DecoderState::Data;
+                    if ascii85 && buf[i..].starts_with(FRAME_START) {
+                        i += FRAME_START.len();
+                        read = i;
+                        continue;
+                    }
+                    if ascii85 && buf[i..] == FRAME_START[..1] && !last {
+                        // wait for the byte after `<`
+                        self.state = DecoderState::Start;
+                        return Ok((result, read));
+                    }
+                    continue;
+                }
+                DecoderState::Data if ascii85 && byte == FRAME_END[0] => {
+                    match buf.get(i + 1) {
+                        Some(&next) if next == FRAME_END[1] => {}
+                        None if !last => return Ok((result, read)),
+                        _ => return Err(invalid_data("expect `>` after `~`".to_string())),
+                    }
+                    decode_group(&group, &mut result)?;
+                    group.clear();
+                    self.state = DecoderState::End;
+                    i += FRAME_END.len();
+                    read = i;
+                    continue;
+                }
+                DecoderState::Data if ascii85 && byte == ZEROS => {
+                    if !group.is_empty() {
+                        return Err(invalid_data(
+                            "`z` in the middle of a base85 group".to_string(),
+                        ));
+                    }
+                    result.extend_from_slice(&[0; 4]);
+                    read = i + 1;
+                }
+                DecoderState::Data => {
+                    let value = self.table[byte as usize];
+                    if value == INVALID {
+                        return Err(invalid_data(format!(
+                            "invalid base85 character `{}`",
+                            (byte as char).escape_default()
+                        )));
+                    }
+                    group.push(value);
+                    if group.len() == 5 {
+                        decode_group(&group, &mut result)?;
+                        group.clear();
+                        read = i + 1;
+                    }
+                }
+                DecoderState::End => {
+                    return Err(invalid_data("data after `~>`".to_string()));
+                }
+            }
+            i += 1;
+        }
+
+        if last {
+            if self.variant == Variant::Z85 && !group.is_empty() {
+                return Err(invalid_data(format!(
+                    "z85 input length must be a multiple of 5, {} character(s) left",
+                    group.len()
+                )));
+            }
+            decode_group(&group, &mut result)?;
+            read = buf.len();
+        }
+        Ok((result, read))
+    }
+}
+
+/// Decodes up to 5 symbol values as up to 4 bytes, a short group being padded
+/// with the highest symbol.
+fn decode_group(values: &[u8], result: &mut Vec<u8>) -> std::io::Result<()> {
+    match values.len() {
+        0 => return Ok(()),
+        1 => {
+            return Err(invalid_data(
+                "base85 group of a single character".to_string(),
+            ))
+        }
+        _ => {}
+    }
+    let value = (0..5).fold(0u64, |value, i| {
+        value * 85 + *values.get(i).unwrap_or(&84) as u64
+    });
+    let value = u32::try_from(value)
+        .map_err(|_| invalid_data("base85 group larger than 2^32".to_string()))?;
+    result.extend_from_slice(&value.to_be_bytes()[..values.len() - 1]);
+    Ok(())
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,325 @@
+use crate::codecs::{Codec, CodecMode, CodecUsage, Example, OptionSpec, Options};
+
+/// Codecs writing the input as a single big number in a radix which is not a
+/// power of 2 (base58, base62, base36), so they work on the whole input.
+pub struct BaseXCodec {
+    kind: BaseXKind,
+}
+
+enum BaseXKind {
+    Base58,
+    /// base58 of the input followed by the first 4 bytes of its double SHA-256
+    #[cfg(feature = "libc")]
+    Base58Check,
+    Base62,
+    Base36,
+}
+
+const BASE58_ALPHABETS: &[&str] = &["bitcoin", "flickr"];
+const BASE58_BITCOIN: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE58_FLICKR: &[u8] = b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const BASE36: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+#[cfg(feature = "libc")]
+const CHECKSUM_LENGTH: usize = 4;
+
+impl BaseXCodec {
+    pub fn new_base58() -> Box<Self> {
+        Box::new(BaseXCodec {
+            kind: BaseXKind::Base58,
+        })
+    }
+
+    #[cfg(feature = "libc")]
+    pub fn new_base58check() -> Box<Self> {
+        Box::new(BaseXCodec {
+            kind: BaseXKind::Base58Check,
+        })
+    }
+
+    pub fn new_base62() -> Box<Self> {
+        Box::new(BaseXCodec {
+            kind: BaseXKind::Base62,
+        })
+    }
+
+    pub fn new_base36() -> Box<Self> {
+        Box::new(BaseXCodec {
+            kind: BaseXKind::Base36,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self.kind {
+            BaseXKind::Base58 => "base58",
+            #[cfg(feature = "libc")]
+            BaseXKind::Base58Check => "base58check",
+            BaseXKind::Base62 => "base62",
+            BaseXKind::Base36 => "base36",
+        }
+    }
+
+    fn alphabet(&self, options: &Options) -> anyhow::Result<Vec<u8>> {
+        let alphabet = match self.kind {
+            BaseXKind::Base58 => base58_alphabet(options)?,
+            #[cfg(feature = "libc")]
+            BaseXKind::Base58Check => base58_alphabet(options)?,
+            BaseXKind::Base62 => BASE62,
+            BaseXKind::Base36 if options.get_switch("c") => {
+                return Ok(BASE36.to_ascii_uppercase());
+            }
+            BaseXKind::Base36 => BASE36,
+        };
+        Ok(alphabet.to_vec())
+    }
+}
+
+fn base58_alphabet(options: &Options) -> anyhow::Result<&'static [u8]> {
+    match options.get_text_str("A")?.unwrap_or("bitcoin") {
+        "bitcoin" => Ok(BASE58_BITCOIN),
+        "flickr" => Ok(BASE58_FLICKR),
+        alphabet => anyhow::bail!(
+            "unknown base58 alphabet: {}, expect one of {}",
+            alphabet,
+            BASE58_ALPHABETS.join(", ")
+        ),
+    }
+}
+
+impl Codec for BaseXCodec {
+    fn run_codec(
+        &self,
+        input: &mut (dyn std::io::Read + Send),
+        global_mode: CodecMode,
+        options: &Options,
+        output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let alphabet = self.alphabet(options)?;
+        let mut buffer = vec![];
+        input.read_to_end(&mut buffer)?;
+
+        match global_mode {
+            CodecMode::Encoding => {
+                #[cfg(feature = "libc")]
+                if let BaseXKind::Base58Check = self.kind {
+                    let checksum = checksum(&buffer);
+                    buffer.extend_from_slice(&checksum);
+                }
+                output.write_all(&encode(&alphabet, &buffer))?;
+            }
+            CodecMode::Decoding => {
+                // base36 is case-insensitive
+                let case_insensitive = matches!(self.kind, BaseXKind::Base36);
+                // e.g. the newline of `echo`
+                let symbols = buffer.trim_ascii_end();
+                let bytes = decode(&alphabet, symbols, case_insensitive)
+                    .map_err(|err| anyhow::anyhow!("{}: {}", self.name(), err))?;
+
+                #[cfg(feature = "libc")]
+                if let BaseXKind::Base58Check = self.kind {
+                    if bytes.len() < CHECKSUM_LENGTH {
+                        anyhow::bail!(
+                            "base58check: {} byte(s) decoded, shorter than the checksum",
+                            bytes.len()
+                        );
+                    }
+                    let (payload, expected) = bytes.split_at(bytes.len() - CHECKSUM_LENGTH);
+                    let actual = checksum(payload);
+                    if actual != expected {
+                        anyhow::bail!(
+                            "base58check: checksum mismatch, expect {}, found {}",
+                            hex::encode(actual),
+                            hex::encode(expected)
+                        );
+                    }
+                    output.write_all(payload)?;
+                    return Ok(());
+                }
+                output.write_all(&bytes)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        let base58_alphabet = || {
+            OptionSpec::text("A", "alphabet", "base58 alphabet")
+                .long("alphabet")
+                .one_of(BASE58_ALPHABETS)
+                .with_default("bitcoin")
+        };
+        Some(match self.kind {
+            BaseXKind::Base58 => vec![base58_alphabet()],
+            #[cfg(feature = "libc")]
+            BaseXKind::Base58Check => vec![base58_alphabet()],
+            BaseXKind::Base62 => vec![],
+            BaseXKind::Base36 => {
+                vec![
+                    OptionSpec::switch("c", "use capital letters (only affects encoding)")
+                        .long("capital"),
+                ]
+            }
+        })
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        match self.kind {
+            #[cfg(feature = "libc")]
+            BaseXKind::Base58Check => Some("libc"),
+            _ => None,
+        }
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        match self.kind {
+            BaseXKind::Base58 => vec![
+                Example::new("base58", "hello world", "StV1DL6CwTryKyV"),
+                Example::new("base58 -A flickr", "hello world", "rTu1dk6cWsRYjYu"),
+                Example::new("hex -d base58", "0000ff", "115Q"),
+                Example::new("base58 -d", "StV1DL6CwTryKyV", "hello world"),
+            ],
+            #[cfg(feature = "libc")]
+            BaseXKind::Base58Check => vec![
+                Example::new(
+                    "hex -d base58check",
+                    "00010966776006953d5567439e5e39f86a0d273bee",
+                    "16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvM",
+                ),
+                Example::new(
+                    "base58check -d hex",
+                    "16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvM",
+                    "00010966776006953d5567439e5e39f86a0d273bee",
+                ),
+            ],
+            BaseXKind::Base62 => vec![
+                Example::new("base62", "hello world", "AAwf93rvy4aWQVw"),
+                Example::new("base62 -d", "AAwf93rvy4aWQVw", "hello world"),
+            ],
+            BaseXKind::Base36 => vec![
+                Example::new("base36", "hello world", "fuvrsivvnfrbjwajo"),
+                Example::new("base36 -c", "hello world", "FUVRSIVVNFRBJWAJO"),
+                Example::new("base36 -d", "FUVRSIVVNFRBJWAJO", "hello world"),
+            ],
+        }
+    }
+}
+
+impl CodecUsage for BaseXCodec {
+    fn usage(&self) -> String {
+        match self.kind {
+            BaseXKind::Base58 => "    base58 encode/decode, input is read as a whole
+    alphabets:
+        bitcoin: 1-9 A-Z a-z without 0 I O l
+        flickr: like bitcoin with lowercase letters first
+"
+            .to_string(),
+            #[cfg(feature = "libc")]
+            BaseXKind::Base58Check => {
+                "    base58 encode/decode with a checksum (e.g. bitcoin addresses), input is
+    read as a whole
+    encoding appends the first 4 bytes of the double sha256 of the input before
+    base58 encoding, decoding verifies and removes them
+"
+                .to_string()
+            }
+            BaseXKind::Base62 => {
+                "    base62 (0-9 A-Z a-z) encode/decode, input is read as a whole\n".to_string()
+            }
+            BaseXKind::Base36 => {
+                "    base36 (0-9 a-z) encode/decode, input is read as a whole, decoding is
+    case-insensitive
+"
+                .to_string()
+            }
+        }
+    }
+}
+
+/// Writes `bytes` as a big-endian number in the radix of `alphabet`, each
+/// leading zero byte as a leading zero symbol.
+fn encode(alphabet: &[u8], bytes: &[u8]) -> Vec<u8> {
+    let radix = alphabet.len() as u32;
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+
+    // little-endian digits in `radix`
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 2);
+    for &byte in &bytes[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % radix) as u8;
+            carry /= radix;
+        }
+        while carry > 0 {
+            digits.push((carry % radix) as u8);
+            carry /= radix;
+        }
+    }
+
+    std::iter::repeat_n(alphabet[0], zeros)
+        .chain(digits.iter().rev().map(|&digit| alphabet[digit as usize]))
+        .collect()
+}
+
+/// Inverse of [`encode`], fails on the first symbol not in `alphabet`.
+fn decode(alphabet: &[u8], symbols: &[u8], case_insensitive: bool) -> anyhow::Result<Vec<u8>> {
+    let radix = alphabet.len() as u32;
+    let mut table = [None; 256];
+    for (value, &symbol) in alphabet.iter().enumerate() {
+        table[symbol as usize] = Some(value as u8);
+        if case_insensitive {
+            table[symbol.to_ascii_lowercase() as usize] = Some(value as u8);
+            table[symbol.to_ascii_uppercase() as usize] = Some(value as u8);
+        }
+    }
+
+    let zeros = symbols
+        .iter()
+        .take_while(|&&symbol| symbol == alphabet[0])
+        .count();
+
+    // little-endian bytes
+    let mut bytes: Vec<u8> = Vec::with_capacity(symbols.len());
+    for (position, &symbol) in symbols.iter().enumerate().skip(zeros) {
+        let Some(value) = table[symbol as usize] else {
+            anyhow::bail!(
+                "invalid symbol `{}` at position {}",
+                (symbol as char).escape_default(),
+                position
+            );
+        };
+        let mut carry = value as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * radix;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    Ok(std::iter::repeat_n(0, zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+#[cfg(feature = "libc")]
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+    use sha2::Digest;
+
+    let hash = sha2::Sha256::digest(sha2::Sha256::digest(bytes));
+    let mut checksum = [0; CHECKSUM_LENGTH];
+    checksum.copy_from_slice(&hash[..CHECKSUM_LENGTH]);
+    checksum
+}
@@ -1,4 +1,4 @@
-use crate::codecs::{Codec, CodecUsage, Options};
+use crate::codecs::{Codec, CodecUsage, OptionSpec, Options};
 
 #[derive(Default)]
 pub struct CatCodecs;
@@ -6,7 +6,7 @@ pub struct CatCodecs;
 impl Codec for CatCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -27,13 +27,18 @@ impl Codec for CatCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::switch("c", "(close input) do not read from input").long("close"),
+            OptionSpec::text("F", "file", "also read from `file`").long("file"),
+        ])
+    }
 }
 
 impl CodecUsage for CatCodecs {
     fn usage(&self) -> String {
         "    (if with no argument, behave like `id`)
-    -c: (close input) do not read from input
-    -F file: also read from `file`, optional
 "
             .to_string()
     }
@@ -1,18 +1,18 @@
-use crate::codecs::{builtins::IdCodecs, Codec, CodecUsage, Options};
+use crate::codecs::{builtins::IdCodecs, Codec, CodecUsage, Example, OptionSpec, Options};
 
 #[derive(Default)]
 pub struct ConstCodecs(IdCodecs);
 
 impl CodecUsage for ConstCodecs {
     fn usage(&self) -> String {
-        "    -C replacement: ingore input, and replace the output with `replacement`\n".to_string()
+        "    ignore input, and replace the output with `replacement`\n".to_string()
     }
 }
 
 impl Codec for ConstCodecs {
     fn run_codec(
         &self,
-        _input: &mut dyn std::io::Read,
+        _input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -27,4 +27,14 @@ impl Codec for ConstCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::text("C", "replacement", "the output")
+            .long("replacement")
+            .required()])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("const -C bye", "hello", "bye")]
+    }
 }
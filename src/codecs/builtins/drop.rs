@@ -1,6 +1,6 @@
 use std::io::Read;
 
-use crate::codecs::{Codec, CodecUsage, Options};
+use crate::codecs::{Codec, CodecUsage, Example, OptionSpec, Options};
 
 #[derive(Default)]
 pub struct DropCodecs;
@@ -8,7 +8,7 @@ pub struct DropCodecs;
 impl Codec for DropCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -24,10 +24,21 @@ impl Codec for DropCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::text("B", "count", "number of bytes")
+            .long("bytes")
+            .int_at_least(0)
+            .with_default("0")])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("drop -B 2", "hello", "llo")]
+    }
 }
 
 impl CodecUsage for DropCodecs {
     fn usage(&self) -> String {
-        "    -B count: drop at most first `count` bytes from input\n".to_string()
+        "    drop at most first `count` bytes from input\n".to_string()
     }
 }
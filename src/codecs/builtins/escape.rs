@@ -1,7 +1,7 @@
 use snailquote::UnescapeError;
 
 use crate::{
-    codecs::{Codec, CodecMode, CodecUsage, Options},
+    codecs::{Codec, CodecMode, CodecUsage, Example, OptionSpec, Options},
     utils::{BytesToBytesDecoder, BytesToBytesEncoder},
 };
 
@@ -11,7 +11,7 @@ pub struct EscapeCodec;
 impl Codec for EscapeCodec {
     fn run_codec(
         &self,
-        mut input: &mut dyn std::io::Read,
+        mut input: &mut (dyn std::io::Read + Send),
         global_mode: CodecMode,
         _options: &Options,
         mut output: &mut dyn std::io::Write,
@@ -65,6 +65,21 @@ impl Codec for EscapeCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("escape", "it's \"quoted\"", "it\\'s \\\"quoted\\\""),
+            Example::new("escape -d", "a\\tb", "a\tb"),
+        ]
+    }
 }
 
 impl CodecUsage for EscapeCodec {
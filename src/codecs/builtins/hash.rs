@@ -1,5 +1,5 @@
 use crate::{
-    codecs::{Codec, CodecMode, CodecUsage, Options},
+    codecs::{Codec, CodecMode, CodecUsage, Example, OptionSpec, Options},
     utils::BytesToBytesEncoder,
 };
 
@@ -40,7 +40,7 @@ impl HashCodec {
 impl Codec for HashCodec {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: CodecMode,
         _options: &Options,
         output: &mut dyn std::io::Write,
@@ -58,6 +58,38 @@ impl Codec for HashCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![])
+    }
+
+    fn modes(&self) -> &'static [CodecMode] {
+        &[CodecMode::Encoding]
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        match self.hash_type {
+            #[cfg(feature = "libc")]
+            HashType::Sha256 => Some("libc"),
+            _ => None,
+        }
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        let (command, output) = match self.hash_type {
+            HashType::Md5 => ("md5 hex", "5d41402abc4b2a76b9719d911017c592"),
+            #[cfg(feature = "libc")]
+            HashType::Sha256 => (
+                "sha256 hex",
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            ),
+            HashType::Sm3 => (
+                "sm3 hex",
+                "becbbfaae6548b8bf0cfcad5a27183cd1be6093b1cceccc303d9c61d0a645268",
+            ),
+        };
+        vec![Example::new(command, "hello", output)]
+    }
 }
 
 impl CodecUsage for HashCodec {
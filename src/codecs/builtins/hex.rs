@@ -1,59 +1,56 @@
 use crate::{
     codecs::Codec,
-    codecs::{CodecUsage, Options},
+    codecs::{CodecUsage, Example, OptionSpec, Options},
     utils::BytesToBytesDecoder,
     utils::BytesToBytesEncoder,
+    utils::DeathRattle,
 };
 
 #[derive(Default)]
 pub struct HexCodec;
 
+const FORMATS: &[&str] = &["plain", "c", "rust"];
+
 impl Codec for HexCodec {
     fn run_codec(
         &self,
-        mut input: &mut dyn std::io::Read,
+        mut input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         options: &Options,
         mut output: &mut dyn std::io::Write,
     ) -> anyhow::Result<()> {
-        let use_capital = options.get_switch("c");
-
         match global_mode {
             crate::codecs::CodecMode::Encoding => {
-                let mut capital_writer;
-                let mut lower_writer;
-
-                let mut writer = if use_capital {
-                    capital_writer = BytesToBytesEncoder::new(&mut output, |buf| {
-                        Ok((hex::encode_upper(buf).into_bytes(), &[]))
-                    });
-                    &mut capital_writer as &mut dyn std::io::Write
-                } else {
-                    lower_writer = BytesToBytesEncoder::new(&mut output, |buf| {
-                        Ok((hex::encode(buf).into_bytes(), &[]))
-                    });
-                    &mut lower_writer as &mut dyn std::io::Write
-                };
+                let (mut formatter, open, close) = Formatter::from_options(options)?;
+                output.write_all(open)?;
 
+                let group = formatter.group;
+                let mut writer = BytesToBytesEncoder::new(&mut output, |buf| {
+                    let (groups, remain) = buf.split_at(buf.len() - buf.len() % group);
+                    Ok((formatter.format(groups), remain))
+                });
                 std::io::copy(input, &mut writer)?;
+
+                writer
+                    .finalize()
+                    .death_rattle(|buf| Ok(Some(formatter.format(buf))))?;
+
+                output.write_all(close)?;
                 Ok(())
             }
             crate::codecs::CodecMode::Decoding => {
                 let mut reader = BytesToBytesDecoder::new(&mut input, |buf| {
-                    let split_at = if buf.len() % 2 == 0 {
-                        buf.len()
-                    } else {
-                        buf.len() - 1
-                    };
-
-                    let (input, remain) = buf.split_at(split_at);
-                    let output = hex::decode(input)
-                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
-
-                    Ok((output, remain))
+                    let (bytes, read) = decode(buf, false)?;
+                    Ok((bytes, &buf[read..]))
                 });
+                reader.set_need_finalize(true);
 
                 std::io::copy(&mut reader, output)?;
+
+                reader.finalize().death_rattle((
+                    |buf| decode(buf, true).map(|(bytes, _)| Some(bytes)),
+                    &mut output,
+                ))?;
                 Ok(())
             }
         }
@@ -61,13 +58,210 @@ impl Codec for HexCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::switch("c", "use capital hex string (only affects encoding)")
+                .long("capital"),
+            OptionSpec::text(
+                "S",
+                "sep",
+                "separator between groups, defaults to a space with -G or -x",
+            )
+            .long("separator"),
+            OptionSpec::text("G", "n", "group `n` bytes together between separators")
+                .long("group")
+                .int()
+                .with_default("1"),
+            OptionSpec::switch("x", "prefix each group with 0x").long("prefix"),
+            OptionSpec::text(
+                "F",
+                "format",
+                "output as plain hex, a C array {0x12, ...} or a rust [u8; N] array [0x12, ...] \
+                 (c and rust exclude -S, -G and -x)",
+            )
+            .long("format")
+            .one_of(FORMATS)
+            .with_default("plain"),
+        ])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("hex", "hi!", "686921"),
+            Example::new("hex -c", "hi?", "68693F"),
+            Example::new("hex -S :", "hi!", "68:69:21"),
+            Example::new("hex -G 2", "hello!", "6865 6c6c 6f21"),
+            Example::new("hex -x -c", "hi?", "0x68 0x69 0x3F"),
+            Example::new("hex -F c", "hi!", "{0x68, 0x69, 0x21}"),
+            Example::new("hex -F rust", "hi!", "[0x68, 0x69, 0x21]"),
+            Example::new("hex -d", "686921", "hi!"),
+            Example::new("hex -d", "68:69 21\n", "hi!"),
+            Example::new("hex -d", "{0x68, 0x69, 0x21}", "hi!"),
+            Example::new("hex -d", "\\x68\\x69\\x21", "hi!"),
+        ]
+    }
 }
 
 impl CodecUsage for HexCodec {
     fn usage(&self) -> String {
         "    binary to hex encode or inverse
-    -c: use capital hex string (only affects encoding)
+    decoding ignores whitespaces, punctuations (e.g. separators and brackets)
+    and 0x or \\x prefixes, but each run of hex digits must have an even length
 "
         .to_string()
     }
 }
+
+/// Writes groups of bytes in hex, separated and prefixed.
+struct Formatter {
+    digits: &'static [u8; 16],
+    separator: Vec<u8>,
+    group: usize,
+    prefix: &'static [u8],
+    /// Whether a group was written, i.e. the next one needs a separator.
+    started: bool,
+}
+
+impl Formatter {
+    /// The formatter, with the text to write before and after the output.
+    fn from_options(options: &Options) -> anyhow::Result<(Self, &'static [u8], &'static [u8])> {
+        let digits = if options.get_switch("c") {
+            b"0123456789ABCDEF"
+        } else {
+            b"0123456789abcdef"
+        };
+        let separator = options.get_text_raw("S");
+        let group = options.get_text::<usize>("G")?;
+        let prefix = options.get_switch("x");
+
+        let (open, close): (&[u8], &[u8]) = match options.get_text_str("F")?.unwrap_or("plain") {
+            "plain" => (b"", b""),
+            "c" => (b"{", b"}"),
+            "rust" => (b"[", b"]"),
+            format => anyhow::bail!(
+                "unknown hex format: {}, expect one of {}",
+                format,
+                FORMATS.join(", ")
+            ),
+        };
+        if !open.is_empty() {
+            if separator.is_some() || group.is_some() || prefix {
+                anyhow::bail!("hex: -F c and -F rust cannot be used with -S, -G or -x");
+            }
+            let formatter = Formatter {
+                digits,
+                separator: b", ".to_vec(),
+                group: 1,
+                prefix: b"0x",
+                started: false,
+            };
+            return Ok((formatter, open, close));
+        }
+
+        if group == Some(0) {
+            anyhow::bail!("hex: group size must be positive");
+        }
+        let separator = match separator {
+            Some(separator) => separator.to_vec(),
+            None if group.is_some() || prefix => b" ".to_vec(),
+            None => vec![],
+        };
+        let formatter = Formatter {
+            digits,
+            separator,
+            group: group.unwrap_or(1),
+            prefix: if prefix { b"0x" } else { b"" },
+            started: false,
+        };
+        Ok((formatter, open, close))
+    }
+
+    fn format(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(bytes.len() * (2 + self.separator.len()));
+        for group in bytes.chunks(self.group) {
+            if self.started {
+                result.extend_from_slice(&self.separator);
+            }
+            self.started = true;
+            result.extend_from_slice(self.prefix);
+            for &byte in group {
+                result.push(self.digits[(byte >> 4) as usize]);
+                result.push(self.digits[(byte & 0xf) as usize]);
+            }
+        }
+        result
+    }
+}
+
+/// Decodes the pairs of hex digits of `buf`, skipping whitespaces,
+/// punctuations and `0x`/`\x` prefixes, and fails on other characters. A digit
+/// without its pair at the end of `buf` is left unread, unless `last`. Returns
+/// the decoded bytes and the length of `buf` read.
+fn decode(buf: &[u8], last: bool) -> std::io::Result<(Vec<u8>, usize)> {
+    let mut result = Vec::with_capacity(buf.len() / 2);
+    let mut read = 0;
+    // the first digit of a pair
+    let mut high: Option<u8> = None;
+    let mut i = 0;
+
+    while i < buf.len() {
+        let byte = buf[i];
+        if high.is_none() && matches!(byte, b'0' | b'\\') {
+            match buf.get(i + 1) {
+                Some(b'x' | b'X') => {
+                    i += 2;
+                    read = i;
+                    continue;
+                }
+                // wait for the next byte to tell whether it is a prefix
+                None if !last => break,
+                _ => {}
+            }
+        }
+
+        match (byte as char).to_digit(16) {
+            Some(digit) => match high.take() {
+                Some(high) => {
+                    result.push(high << 4 | digit as u8);
+                    read = i + 1;
+                }
+                None => high = Some(digit as u8),
+            },
+            None if byte.is_ascii_whitespace()
+                || (byte.is_ascii_punctuation() && byte != b'\\') =>
+            {
+                if high.is_some() {
+                    return Err(odd_digits());
+                }
+                read = i + 1;
+            }
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "invalid hex character `{}`",
+                        (byte as char).escape_default()
+                    ),
+                ))
+            }
+        }
+        i += 1;
+    }
+
+    if last {
+        if high.is_some() {
+            return Err(odd_digits());
+        }
+        read = buf.len();
+    }
+    Ok((result, read))
+}
+
+fn odd_digits() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, "odd number of hex digits")
+}
@@ -1,7 +1,10 @@
 #[cfg(feature = "libc")]
 mod aes;
 mod append;
+mod base32;
 mod base64;
+mod base85;
+mod basex;
 mod cat;
 mod r#const;
 mod drop;
@@ -21,7 +24,10 @@ mod zlib;
 
 #[cfg(feature = "libc")]
 pub use self::aes::*;
+pub use self::base32::*;
 pub use self::base64::*;
+pub use self::base85::*;
+pub use self::basex::*;
 pub use self::hex::*;
 #[cfg(feature = "libc")]
 pub use self::rsa::*;
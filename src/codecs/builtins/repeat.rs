@@ -1,5 +1,5 @@
 use crate::{
-    codecs::{Codec, CodecUsage, Options},
+    codecs::{Codec, CodecUsage, Example, OptionSpec, Options},
     utils::MultiWriter,
 };
 
@@ -12,14 +12,14 @@ pub struct IdCodecs(RepeatCodecs);
 
 impl CodecUsage for RepeatCodecs {
     fn usage(&self) -> String {
-        "    -T times: repeat input for `times` times (int, >=0, default 0)".to_string()
+        "    repeat input for `times` times\n".to_string()
     }
 }
 
 impl Codec for RepeatCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -36,7 +36,7 @@ impl Codec for RepeatCodecs {
             let _ = std::io::copy(input, &mut writer)?;
 
             for _ in 1..times {
-                let _ = output.write_all(&buffer)?;
+                output.write_all(&buffer)?;
             }
         }
 
@@ -46,18 +46,29 @@ impl Codec for RepeatCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::text("T", "times", "repeat times")
+            .long("times")
+            .int_at_least(0)
+            .with_default("0")])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("repeat -T 3", "ab", "ababab")]
+    }
 }
 
 impl CodecUsage for IdCodecs {
     fn usage(&self) -> String {
-        "    pass input to output as is".to_string()
+        "    pass input to output as is\n".to_string()
     }
 }
 
 impl Codec for IdCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         _options: &Options,
         output: &mut dyn std::io::Write,
@@ -71,4 +82,20 @@ impl Codec for IdCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn expansion(&self, _options: &Options) -> Option<String> {
+        Some("repeat -T 1".to_string())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("id", "hello", "hello")]
+    }
 }
@@ -7,7 +7,7 @@ use rsa::{
 use rsa::rand_core::CryptoRngCore;
 use rsa::traits::PaddingScheme;
 
-use crate::codecs::{Codec, CodecUsage};
+use crate::codecs::{Codec, CodecUsage, OptionSpec};
 
 #[derive(Default)]
 pub struct RsaCryptCodec;
@@ -18,7 +18,7 @@ pub struct RsaSignCodec;
 impl Codec for RsaCryptCodec {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         options: &crate::codecs::Options,
         output: &mut dyn std::io::Write,
@@ -54,17 +54,47 @@ impl Codec for RsaCryptCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::text(
+                "PK",
+                "pub_key",
+                "public key pem string, default pkcs1 format",
+            )
+            .long("public-key"),
+            OptionSpec::text(
+                "SK",
+                "pri_key",
+                "private key pem string, default pkcs1 format",
+            )
+            .long("private-key")
+            .sensitive(),
+            OptionSpec::switch("8", "use pkcs8 key format instead of pkcs1").long("pkcs8"),
+            OptionSpec::switch("dr", "use der format instead of pem").long("der"),
+            OptionSpec::text("PS", "scheme", "padding scheme")
+                .long("padding")
+                .one_of(&["oaep", "pkcs15"])
+                .with_default("oaep"),
+            OptionSpec::text(
+                "H",
+                "algorithm",
+                "hash algorithm used for oaep padding scheme",
+            )
+            .long("hash")
+            .one_of(&["sha1", "sha256"])
+            .with_default("sha256"),
+        ])
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        Some("libc")
+    }
 }
 
 impl CodecUsage for RsaCryptCodec {
     fn usage(&self) -> String {
         "    rsa encryption with public key and decryption with private key
-    -PK pub_key: public key pem string, default pkcs1 format
-    -SK pri_key: private key pem string, default pkcs1 format
-    -8: use pkcs8 key format instead of pkcs1
-    -dr: use der format instead of pem
-    -PS scheme: padding scheme (oaep, pkcs15; defaults to oaep)
-    -H algorithm: hash algorithm used for oaep padding scheme (sha1, sha256; defaults to sha256)
 "
         .to_string()
     }
@@ -73,7 +103,7 @@ impl CodecUsage for RsaCryptCodec {
 impl Codec for RsaSignCodec {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         options: &crate::codecs::Options,
         output: &mut dyn std::io::Write,
@@ -115,6 +145,35 @@ impl Codec for RsaSignCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::text(
+                "PK",
+                "pub_key",
+                "public key pem string or der bytes, default pkcs1 format",
+            )
+            .long("public-key"),
+            OptionSpec::text(
+                "SK",
+                "pri_key",
+                "private key pem string or der bytes, default pkcs1 format",
+            )
+            .long("private-key")
+            .sensitive(),
+            OptionSpec::switch("8", "use pkcs8 key format instead of pkcs1").long("pkcs8"),
+            OptionSpec::switch("dr", "use der format instead of pem").long("der"),
+            OptionSpec::text("H", "algorithm", "hash algorithm used for sign")
+                .long("hash")
+                .one_of(&["sha1", "sha256"]),
+            OptionSpec::text("S", "signature", "signature to verify (decoding only)")
+                .long("signature"),
+        ])
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        Some("libc")
+    }
 }
 
 impl CodecUsage for RsaSignCodec {
@@ -123,12 +182,9 @@ impl CodecUsage for RsaSignCodec {
     NOTE:
         1. input must first be hashed in algorithm specified in -H option
             e.g. sha256 rsa-sign -SK sk_string -H sha256
-        2. for verification, output nothing if succeeded, error if not (pending to change along with new `if` meta codec)
-    -PK pub_key: public key pem string or der bytes, default pkcs1 format
-    -SK pri_key: private key pem string or der bytes, default pkcs1 format
-    -8: use pkcs8 key format instead of pkcs1
-    -dr: use der format instead of pem
-    -H algorithm: hash algorithm used for sign (sha1, sha256)
+        2. for verification, output nothing if succeeded, error if not. use it as the condition of
+            `if` to branch on the result instead, e.g.
+            if -C [rsa-sign -d -PK pk_string -S signature] -T [const -C valid] -E [const -C invalid]
 "
             .to_string()
     }
@@ -1,6 +1,7 @@
 use crate::{
     codecs::Codec,
-    codecs::{CodecUsage, Options},
+    codecs::{CodecUsage, Example, OptionSpec, Options},
+    executor::commands,
     utils::MultiWriter,
 };
 
@@ -10,7 +11,7 @@ pub struct TeeCodecs;
 impl Codec for TeeCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -36,13 +37,18 @@ impl Codec for TeeCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::switch("c", "(close output) do not write to output").long("close"),
+            OptionSpec::text("O", "file", "also write to `file`").long("file"),
+        ])
+    }
 }
 
 impl CodecUsage for TeeCodecs {
     fn usage(&self) -> String {
         "    (if with no argument, behave like `id`)
-    -c: (close output) do not write to output
-    -O file: also write to `file`, optional
 "
         .to_string()
     }
@@ -54,7 +60,7 @@ pub struct SinkCodecs(TeeCodecs);
 impl Codec for SinkCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         _options: &Options,
         output: &mut dyn std::io::Write,
@@ -67,6 +73,18 @@ impl Codec for SinkCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![])
+    }
+
+    fn expansion(&self, _options: &Options) -> Option<String> {
+        Some("tee -c".to_string())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("sink", "hello", "")]
+    }
 }
 
 impl CodecUsage for SinkCodecs {
@@ -89,7 +107,7 @@ pub struct RedirectCodecs(TeeCodecs);
 impl Codec for RedirectCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -106,13 +124,25 @@ impl Codec for RedirectCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::text(
+            "O",
+            "file",
+            "redirect output to `file`",
+        )
+        .long("file")
+        .required()])
+    }
+
+    fn expansion(&self, options: &Options) -> Option<String> {
+        let file = commands::Text::Bytes(options.get_text_raw("O")?.to_vec());
+        Some(format!("tee -c -O {}", file))
+    }
 }
 
 impl CodecUsage for RedirectCodecs {
     fn usage(&self) -> String {
-        "    = tee -c -O `file`
-    -O file: redirect output to `file`
-"
-        .to_string()
+        "    = tee -c -O `file`\n".to_string()
     }
 }
@@ -1,12 +1,15 @@
 use std::mem::MaybeUninit;
-use std::{io::ErrorKind, process::Stdio};
+use std::{
+    io::ErrorKind,
+    process::{ExitStatus, Stdio},
+};
 
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     process::Command,
 };
 
-use crate::codecs::{Codec, CodecUsage};
+use crate::codecs::{Codec, CodecUsage, OptionSpec};
 
 #[derive(Default)]
 pub struct SystemCodec;
@@ -14,7 +17,7 @@ pub struct SystemCodec;
 impl Codec for SystemCodec {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &crate::codecs::Options,
         output: &mut dyn std::io::Write,
@@ -29,71 +32,87 @@ impl Codec for SystemCodec {
             .split(' ')
             .filter(|s| !s.is_empty());
 
-        {
-            let mut input = AsyncReadWrapper(input);
-            let mut output = AsyncWriteWrapper(output);
-
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_io()
-                .build()?;
-
-            rt.block_on(async {
-                let mut child = Command::new(command_name)
-                    .args(args)
-                    .stdin(Stdio::piped())
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::inherit())
-                    .spawn()?;
-
-                let mut stdin = child
-                    .stdin
-                    .take()
-                    .ok_or_else(|| anyhow::anyhow!("system: failed to open stdin"))?;
-
-                let mut stdout = child
-                    .stdout
-                    .take()
-                    .ok_or_else(|| anyhow::anyhow!("system: failed to open stdout"))?;
-
-                let read_handle = async {
-                    let _ = tokio::io::copy(&mut input, &mut stdin).await?;
-                    drop(stdin);
-                    anyhow::Result::<_>::Ok(())
-                };
-
-                let write_handle = async {
-                    let _ = tokio::io::copy(&mut stdout, &mut output).await?;
-                    drop(stdout);
-                    Ok(())
-                };
-
-                let child_handle = async {
-                    let _ = child.wait().await?;
-                    Ok(())
-                };
-
-                tokio::try_join!(write_handle, read_handle, child_handle)?;
-                Ok::<_, anyhow::Error>(())
-            })?;
-        }
+        let mut command = Command::new(command_name);
+        command.args(args);
+        run_command(command, input, output)?;
 
         Ok(())
     }
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::text("C", "command", "command to run")
+                .long("command")
+                .required(),
+            OptionSpec::text("A", "args", "args for command, separated by spaces").long("args"),
+        ])
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        Some("system")
+    }
 }
 
 impl CodecUsage for SystemCodec {
     fn usage(&self) -> String {
         "   execute command, pipe its stdin as input, stdout as output
-    -C command: command to run
-    -A args: args for command
 "
         .to_string()
     }
 }
 
+/// Runs `command`, piping `input` to its stdin and its stdout to `output`.
+pub(crate) fn run_command(
+    mut command: Command,
+    input: &mut dyn std::io::Read,
+    output: &mut dyn std::io::Write,
+) -> anyhow::Result<ExitStatus> {
+    let mut input = AsyncReadWrapper(input);
+    let mut output = AsyncWriteWrapper(output);
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()?;
+
+    rt.block_on(async {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("failed to open stdin"))?;
+
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("failed to open stdout"))?;
+
+        let read_handle = async {
+            let _ = tokio::io::copy(&mut input, &mut stdin).await?;
+            drop(stdin);
+            anyhow::Result::<_>::Ok(())
+        };
+
+        let write_handle = async {
+            let _ = tokio::io::copy(&mut stdout, &mut output).await?;
+            drop(stdout);
+            Ok(())
+        };
+
+        let child_handle = async { Ok(child.wait().await?) };
+
+        let (_, _, status) = tokio::try_join!(write_handle, read_handle, child_handle)?;
+        Ok(status)
+    })
+}
+
 struct AsyncReadWrapper<'a>(&'a mut dyn std::io::Read);
 
 // impl AsyncReadWrapper<'_> {
@@ -1,6 +1,6 @@
 use std::io::Read;
 
-use crate::codecs::{Codec, CodecUsage, Options};
+use crate::codecs::{Codec, CodecUsage, Example, OptionSpec, Options};
 
 #[derive(Default)]
 pub struct TakeCodecs;
@@ -8,7 +8,7 @@ pub struct TakeCodecs;
 impl Codec for TakeCodecs {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -22,10 +22,21 @@ impl Codec for TakeCodecs {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::text("B", "count", "number of bytes")
+            .long("bytes")
+            .int_at_least(0)
+            .with_default("0")])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("take -B 2", "hello", "he")]
+    }
 }
 
 impl CodecUsage for TakeCodecs {
     fn usage(&self) -> String {
-        "    -B count: take up to first `count` bytes from input\n".to_string()
+        "    take up to first `count` bytes from input\n".to_string()
     }
 }
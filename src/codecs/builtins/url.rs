@@ -1,7 +1,7 @@
 use percent_encoding::AsciiSet;
 
 use crate::{
-    codecs::{Codec, CodecMode, CodecUsage, Options},
+    codecs::{Codec, CodecMode, CodecUsage, Example, OptionSpec, Options},
     utils::{BytesToBytesDecoder, BytesToBytesEncoder},
 };
 
@@ -10,16 +10,14 @@ pub struct UrlCodec;
 
 impl CodecUsage for UrlCodec {
     fn usage(&self) -> String {
-        "    url query escape/unescape
-    -p: use path escape instead of query escape"
-            .to_string()
+        "    url query escape/unescape\n".to_string()
     }
 }
 
 impl Codec for UrlCodec {
     fn run_codec(
         &self,
-        mut input: &mut dyn std::io::Read,
+        mut input: &mut (dyn std::io::Read + Send),
         global_mode: CodecMode,
         options: &Options,
         mut output: &mut dyn std::io::Write,
@@ -66,6 +64,26 @@ impl Codec for UrlCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::switch(
+            "p",
+            "use path escape instead of query escape",
+        )
+        .long("path")])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("url", "a&b/c?", "a%26b%2Fc%3F"),
+            Example::new("url -p", "a&b/c?", "a&b%2Fc%3F"),
+            Example::new("url -d", "a%26b%2Fc%3F", "a&b/c?"),
+        ]
+    }
 }
 
 const QUERY_SET: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
@@ -1,6 +1,6 @@
 use flate2::Compression;
 
-use crate::codecs::{Codec, CodecMode, CodecUsage, Options};
+use crate::codecs::{Codec, CodecMode, CodecUsage, Example, OptionSpec, Options};
 
 #[derive(Default)]
 pub struct ZlibCodec;
@@ -8,7 +8,7 @@ pub struct ZlibCodec;
 impl Codec for ZlibCodec {
     fn run_codec(
         &self,
-        input: &mut dyn std::io::Read,
+        input: &mut (dyn std::io::Read + Send),
         global_mode: CodecMode,
         options: &Options,
         output: &mut dyn std::io::Write,
@@ -37,10 +37,32 @@ impl Codec for ZlibCodec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         Some(self)
     }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::text("L", "level", "compress level")
+            .long("level")
+            .int_range(0, 9)
+            .with_default("6")])
+    }
+
+    fn is_invertible(&self) -> bool {
+        true
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        Some("libc")
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("zlib base64", "hello", "eJzLSM3JyQcABiwCFQ=="),
+            Example::new("base64 -d zlib -d", "eJzLSM3JyQcABiwCFQ==", "hello"),
+        ]
+    }
 }
 
 impl CodecUsage for ZlibCodec {
     fn usage(&self) -> String {
-        "    -L level: compress level (int, [0, 9], default 6)\n".to_string()
+        "    zlib compress/decompress\n".to_string()
     }
 }
@@ -0,0 +1,268 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    codecs::{
+        Codec, CodecMetaInfo, CodecMode, CodecUsage, OptionKind, OptionSpec, Options, ValueType,
+    },
+    executor::{commands, parser, Chain},
+};
+
+const PLACEHOLDER_PREFIX: &str = "$";
+
+/// A named chain defined by the user, e.g. in the aliases file:
+///
+/// ```text
+/// zb64 = zlib base64
+/// aesdec = base64 -d aes-cbc -d -K $K -IV $IV
+/// ```
+///
+/// Option values of the form `$X` are placeholders, replaced by the value of
+/// option `-X` passed to the alias (`$$` escapes a literal `$`).
+pub struct AliasCodec {
+    name: String,
+    definition: Vec<String>,
+}
+
+impl AliasCodec {
+    pub fn new(name: &str, definition: Vec<String>) -> Box<Self> {
+        Box::new(AliasCodec {
+            name: name.to_string(),
+            definition,
+        })
+    }
+}
+
+impl Codec for AliasCodec {
+    fn run_codec(
+        &self,
+        input: &mut (dyn std::io::Read + Send),
+        global_mode: CodecMode,
+        options: &Options,
+        output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let mut codecs = self.parse()?;
+
+        for codec in &mut codecs {
+            self.substitute(codec, options)?;
+        }
+
+        Chain::new(codecs)
+            .engine(options.engine())
+            .run(input, global_mode, output)?;
+        Ok(())
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn expansion(&self, _options: &Options) -> Option<String> {
+        let codecs: Vec<_> = self.parse().ok()?.iter().map(ToString::to_string).collect();
+        Some(codecs.join(" "))
+    }
+
+    /// Each placeholder `$X` of the definition is a required option `-X`,
+    /// sensitive as it may be a key of the aliased codecs.
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        let mut options: Vec<OptionSpec> = vec![];
+        for word in &self.definition {
+            let Some(placeholder) = word.strip_prefix(PLACEHOLDER_PREFIX) else {
+                continue;
+            };
+            if placeholder.is_empty()
+                || placeholder.starts_with(PLACEHOLDER_PREFIX)
+                || options.iter().any(|option| option.name == placeholder)
+            {
+                continue;
+            }
+
+            let kind = OptionKind::Text {
+                value_name: "value",
+                value_type: ValueType::Bytes,
+            };
+            options.push(
+                OptionSpec::new(placeholder.to_string().into(), kind, "placeholder".into())
+                    .required()
+                    .sensitive(),
+            );
+        }
+        Some(options)
+    }
+}
+
+impl CodecUsage for AliasCodec {
+    fn usage(&self) -> String {
+        format!("    (alias) = {}\n", self.definition.join(" "))
+    }
+}
+
+impl AliasCodec {
+    // parsed lazily, so that aliases may refer to codecs registered after them
+    fn parse(&self) -> anyhow::Result<Vec<commands::Codec>> {
+        let mut tokenizer = parser::Tokenizer::new(self.definition.clone());
+        parser::parse_codecs(&mut tokenizer)
+            .map_err(|err| anyhow::anyhow!("{}: invalid alias definition: {}", self.name, err))
+    }
+
+    fn substitute(&self, codec: &mut commands::Codec, options: &Options) -> anyhow::Result<()> {
+        for option in &mut codec.options {
+            let commands::CommandOption::Value { text, .. } = option else {
+                continue;
+            };
+            match text {
+                commands::Text::String(value) => {
+                    if let Some(placeholder) = value.strip_prefix(PLACEHOLDER_PREFIX) {
+                        *text = if placeholder.starts_with(PLACEHOLDER_PREFIX) {
+                            commands::Text::String(placeholder.to_string())
+                        } else {
+                            let value = options.get_text_raw(placeholder).ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "{}: missing required option -{}",
+                                    self.name,
+                                    placeholder
+                                )
+                            })?;
+                            commands::Text::Bytes(value.to_vec())
+                        };
+                    }
+                }
+                commands::Text::Codecs { codecs, .. } | commands::Text::Chain(codecs) => {
+                    for codec in codecs {
+                        self.substitute(codec, options)?;
+                    }
+                }
+                commands::Text::Bytes(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The aliases file: `$CODEC_ALIASES`, or `codec/aliases` in the user config
+/// directory (`$XDG_CONFIG_HOME` or `~/.config`).
+pub fn default_aliases_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("CODEC_ALIASES") {
+        return Some(path.into());
+    }
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_dir.join("codec").join("aliases"))
+}
+
+/// Registers the aliases defined in `path`, one `name = codecs` per line.
+/// Empty lines and `#` comments are ignored. A missing file defines no alias.
+pub fn load_aliases(meta_info: &mut CodecMetaInfo, path: &Path) -> anyhow::Result<()> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut aliases = vec![];
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (name, definition) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expect `name = codecs`", line_no + 1))?;
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            anyhow::bail!("line {}: invalid alias name `{}`", line_no + 1, name);
+        }
+        if meta_info.lookup(name).is_some() {
+            anyhow::bail!("line {}: `{}` is already defined", line_no + 1, name);
+        }
+
+        let definition = parser::split_str(definition)
+            .map_err(|err| anyhow::anyhow!("line {}: {}", line_no + 1, err))?;
+        meta_info.register(name, AliasCodec::new(name, definition.clone()));
+        aliases.push((name.to_string(), definition));
+    }
+
+    check_cycles(meta_info, &aliases)
+}
+
+/// Fails if an alias of `aliases` refers to itself, directly or through other
+/// aliases, as expanding it would never end.
+fn check_cycles(
+    meta_info: &CodecMetaInfo,
+    aliases: &[(String, Vec<String>)],
+) -> anyhow::Result<()> {
+    let graph: HashMap<&str, Vec<String>> = aliases
+        .iter()
+        .map(|(name, definition)| {
+            let mut references = vec![];
+            // an invalid definition is reported when the alias is run
+            let mut tokenizer = parser::Tokenizer::with_meta_info(definition.clone(), meta_info);
+            if let Ok(codecs) = parser::parse_codecs(&mut tokenizer) {
+                collect_references(&codecs, &mut references);
+            }
+            (name.as_str(), references)
+        })
+        .collect();
+
+    let mut done = HashSet::new();
+    for (name, _) in aliases {
+        let mut path = vec![];
+        if let Some(cycle) = find_cycle(name, &graph, &mut path, &mut done) {
+            if cycle.len() == 2 {
+                anyhow::bail!("alias `{}` refers to itself", cycle[0]);
+            }
+            anyhow::bail!("aliases refer to each other: {}", cycle.join(" -> "));
+        }
+    }
+    Ok(())
+}
+
+/// Adds the names of `codecs` and of the codecs in their options.
+fn collect_references(codecs: &[commands::Codec], references: &mut Vec<String>) {
+    for codec in codecs {
+        references.push(codec.name.clone());
+        for option in &codec.options {
+            if let commands::CommandOption::Value {
+                text: commands::Text::Codecs { codecs, .. } | commands::Text::Chain(codecs),
+                ..
+            } = option
+            {
+                collect_references(codecs, references);
+            }
+        }
+    }
+}
+
+/// Depth-first search of a cycle from `name`, returned as the path from its
+/// first alias back to it. `done` holds the aliases known to be out of any
+/// cycle.
+fn find_cycle<'a>(
+    name: &'a str,
+    graph: &'a HashMap<&str, Vec<String>>,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if let Some(start) = path.iter().position(|alias| *alias == name) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(name);
+        return Some(cycle);
+    }
+    let references = graph.get(name)?;
+    if done.contains(name) {
+        return None;
+    }
+
+    path.push(name);
+    for reference in references {
+        if let Some(cycle) = find_cycle(reference, graph, path, done) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    done.insert(name);
+    None
+}
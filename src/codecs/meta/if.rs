@@ -0,0 +1,87 @@
+use crate::codecs::{
+    CodecMetaInfo, CodecMode, CodecUsage, Example, MetaCodec, OptionSpec, Options,
+};
+
+#[derive(Default)]
+pub struct IfMetaCodec;
+
+impl MetaCodec for IfMetaCodec {
+    fn run_meta_codec(
+        &self,
+        input: &mut (dyn std::io::Read + Send),
+        global_mode: CodecMode,
+        options: &Options,
+        _codec_meta_info: &CodecMetaInfo,
+        output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let condition = options
+            .get_chain("C")
+            .ok_or_else(|| anyhow::anyhow!("if: missing required option condition chain (-C)"))?;
+
+        let mut buffer = Vec::<u8>::with_capacity(1024 * 8);
+        std::io::copy(input, &mut buffer)?;
+
+        let mut condition_output = Vec::new();
+        let result = condition
+            .run(&mut buffer.as_slice(), global_mode, &mut condition_output)
+            .is_ok()
+            && (!options.get_switch("o") || is_truthy(&condition_output));
+
+        let branch = if result {
+            options.get_chain("T")
+        } else {
+            options.get_chain("E")
+        };
+
+        match branch {
+            Some(chain) => chain.run(&mut buffer.as_slice(), global_mode, output)?,
+            None => output.write_all(&buffer)?,
+        }
+
+        Ok(())
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::chain("C", "condition chain")
+                .long("condition")
+                .required(),
+            OptionSpec::chain("T", "then chain, defaults to `id`").long("then"),
+            OptionSpec::chain("E", "else chain, defaults to `id`").long("else"),
+            OptionSpec::switch(
+                "o",
+                "(output) also require the output of the condition chain to be truthy, i.e. not
+        empty, `0` or `false` (ignoring surrounding whitespaces)",
+            )
+            .long("output"),
+        ])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        let command = "if -C [take -B 1] -o -T [const -C non-empty] -E [const -C empty]";
+        vec![
+            Example::new(command, "", "empty"),
+            Example::new(command, "x", "non-empty"),
+        ]
+    }
+}
+
+impl CodecUsage for IfMetaCodec {
+    fn usage(&self) -> String {
+        "    run the condition chain on input, then pass input to the then chain if it succeeded,
+    or to the else chain if it failed. chains are written as [codecs] and run in the
+    current coding mode, e.g.:
+    if -C [zlib -d] -T [zlib -d]
+        decompress input only if it is valid zlib data
+"
+        .to_string()
+    }
+}
+
+fn is_truthy(output: &[u8]) -> bool {
+    !matches!(output.trim_ascii(), b"" | b"0" | b"false")
+}
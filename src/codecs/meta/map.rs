@@ -0,0 +1,111 @@
+use std::io::{BufRead, BufReader};
+
+use crate::{
+    codecs::{CodecMetaInfo, CodecMode, CodecUsage, Example, MetaCodec, OptionSpec, Options},
+    executor::Engine,
+};
+
+#[derive(Default)]
+pub struct MapMetaCodec;
+
+impl MetaCodec for MapMetaCodec {
+    fn run_meta_codec(
+        &self,
+        input: &mut (dyn std::io::Read + Send),
+        global_mode: CodecMode,
+        options: &Options,
+        _codec_meta_info: &CodecMetaInfo,
+        output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        // records are small, avoid starting threads for each one of them
+        let chain = options
+            .get_chain("C")
+            .ok_or_else(|| anyhow::anyhow!("map: missing required option chain (-C)"))?
+            .clone()
+            .engine(Engine::Sequential);
+        let separator = if options.get_switch("z") {
+            b"\0"
+        } else {
+            options.get_text_raw("S").unwrap_or(b"\n")
+        };
+        if separator.is_empty() {
+            anyhow::bail!("map: separator cannot be empty");
+        }
+
+        let mut reader = BufReader::new(input);
+        let mut record = Vec::new();
+        for index in 1.. {
+            record.clear();
+            let terminated = read_record(&mut reader, separator, &mut record)?;
+            if record.is_empty() && !terminated {
+                break;
+            }
+
+            chain
+                .run(&mut record.as_slice(), global_mode, output)
+                .map_err(|err| anyhow::anyhow!("map: record {}: {}", index, err))?;
+
+            if !terminated {
+                break;
+            }
+            output.write_all(separator)?;
+        }
+
+        Ok(())
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::chain("C", "the chain to run on each record")
+                .long("chain")
+                .required(),
+            OptionSpec::text("S", "separator", "record separator")
+                .long("separator")
+                .with_default("\\n"),
+            OptionSpec::switch("z", "use NUL as record separator (= -S ['\\0' escape -d])")
+                .long("null"),
+        ])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example::new("map -C [base64]", "a\nb\n", "YQ==\nYg==\n"),
+            Example::new("map -S , -C [hex]", "a,b", "61,62"),
+        ]
+    }
+}
+
+impl CodecUsage for MapMetaCodec {
+    fn usage(&self) -> String {
+        "    split input into records, run the chain on each record, and join the outputs with
+    the separator. a trailing separator in input is kept in output, e.g.:
+    map -C [base64 -d]
+        decode a file with one base64 string per line
+"
+        .to_string()
+    }
+}
+
+/// Reads the next record, without the separator, into `record`. Returns
+/// whether the record was terminated by `separator` (instead of the end of
+/// input).
+fn read_record(
+    reader: &mut impl BufRead,
+    separator: &[u8],
+    record: &mut Vec<u8>,
+) -> std::io::Result<bool> {
+    let last = separator[separator.len() - 1];
+    loop {
+        if reader.read_until(last, record)? == 0 || record.last() != Some(&last) {
+            return Ok(false);
+        }
+        if record.ends_with(separator) {
+            record.truncate(record.len() - separator.len());
+            return Ok(true);
+        }
+    }
+}
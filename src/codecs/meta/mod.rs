@@ -1,3 +1,11 @@
+mod alias;
+mod r#if;
+mod map;
 mod usage;
+mod verify;
 
-pub use self::usage::*;
\ No newline at end of file
+pub use self::alias::*;
+pub use self::map::*;
+pub use self::r#if::*;
+pub use self::usage::*;
+pub use self::verify::*;
@@ -1,4 +1,9 @@
-use crate::codecs::MetaCodec;
+use crate::{
+    codecs::{Codec, MetaCodec, OptionKind, OptionSpec, ValueType},
+    utils::{json_string, shell_quote},
+};
+
+const FORMATS: &[&str] = &["text", "json", "markdown", "man"];
 
 #[derive(Default)]
 pub struct UsageMetaCodec;
@@ -6,36 +11,425 @@ pub struct UsageMetaCodec;
 impl MetaCodec for UsageMetaCodec {
     fn run_meta_codec(
         &self,
-        _input: &mut dyn std::io::Read,
+        _input: &mut (dyn std::io::Read + Send),
         _global_mode: crate::codecs::CodecMode,
         options: &crate::codecs::Options,
         codec_meta_info: &crate::codecs::CodecMetaInfo,
         output: &mut dyn std::io::Write,
     ) -> anyhow::Result<()> {
         let codec_name = options.get_text_str("C")?;
+        let format = options.get_text_str("f")?.unwrap_or("text");
 
-        if let Some(codec_name) = codec_name {
-            let codec = codec_meta_info
-                .codecs_map
-                .get(codec_name)
-                .ok_or_else(|| anyhow::anyhow!("codec not found: {}", codec_name))?;
-            writeln!(output, "{}", codec_name)?;
+        let codecs: Vec<(&str, &dyn Codec)> = match codec_name {
+            Some(codec_name) => {
+                let codec = codec_meta_info
+                    .codecs_map
+                    .get(codec_name)
+                    .ok_or_else(|| anyhow::anyhow!("codec not found: {}", codec_name))?;
+                vec![(codec_name, codec.as_ref() as &dyn Codec)]
+            }
+            None => codec_meta_info
+                .codecs_iter()
+                .map(|(name, codec)| (name.as_str(), codec.as_ref() as &dyn Codec))
+                .collect(),
+        };
 
-            if let Some(usage) = codec.as_codec_usage() {
-                writeln!(output, "{}", usage.usage())?;
+        match format {
+            "text" => {
+                if codec_name.is_none() {
+                    writeln!(output, "Available codecs:")?;
+                }
+                for (name, codec) in codecs {
+                    write_usage(output, name, codec)?;
+                }
             }
-        } else {
-            writeln!(output, "Available codecs:")?;
+            "json" => write_json(output, &codecs)?,
+            "markdown" => write_markdown(output, &codecs)?,
+            "man" => write_man(output, &codecs)?,
+            _ => anyhow::bail!("unknown format: {}", format),
+        }
 
-            for (name, codec) in codec_meta_info.codecs_iter() {
-                writeln!(output, "{}", name)?;
+        Ok(())
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![
+            OptionSpec::text(
+                "C",
+                "codec",
+                "show usage of `codec` only, defaults to all codecs",
+            )
+            .long("codec"),
+            OptionSpec::text("f", "format", "output format")
+                .long("format")
+                .one_of(FORMATS)
+                .with_default("text"),
+        ])
+    }
+}
+
+/// Writes the name, the description and the generated option lines of `codec`,
+/// followed by an empty line.
+fn write_usage(
+    output: &mut dyn std::io::Write,
+    name: &str,
+    codec: &dyn Codec,
+) -> anyhow::Result<()> {
+    writeln!(output, "{}", name)?;
+
+    if let Some(usage) = codec.as_codec_usage() {
+        write!(output, "{}", usage.usage())?;
+    }
+    for option in codec.options().unwrap_or_default() {
+        write!(output, "{}", option.usage())?;
+    }
+    let examples = codec.examples();
+    if !examples.is_empty() {
+        writeln!(output, "    examples:")?;
+        for example in examples {
+            writeln!(
+                output,
+                "        $ {}",
+                example_command_line(example.command, example.input)
+            )?;
+            for line in example.output.lines() {
+                writeln!(output, "        {}", line)?;
+            }
+        }
+    }
+    writeln!(output)?;
+
+    Ok(())
+}
 
-                if let Some(usage) = codec.as_codec_usage() {
-                    writeln!(output, "{}", usage.usage())?;
+/// The usage text of `codec` without its indentation.
+fn description(codec: &dyn Codec) -> String {
+    let Some(usage) = codec.as_codec_usage() else {
+        return String::new();
+    };
+    let usage = usage.usage();
+    let indent = usage
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+
+    let lines: Vec<&str> = usage
+        .lines()
+        .map(|line| line.get(indent..).unwrap_or_default().trim_end())
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+fn modes(codec: &dyn Codec) -> String {
+    let modes: Vec<String> = codec.modes().iter().map(ToString::to_string).collect();
+    let mut modes = modes.join(", ");
+    if codec.is_invertible() {
+        modes.push_str(" (invertible)");
+    }
+    modes
+}
+
+/// The command line of an example, e.g. `printf %s 'hi' | codec base64`, or
+/// `printf '%b' 'a\r\n' | codec hex` if the input has control characters, so
+/// that the command line is a single printable line.
+fn example_command_line(command: &str, input: &str) -> String {
+    if !input.contains(|c: char| c.is_control()) {
+        return format!("printf %s {} | codec {}", shell_quote(input), command);
+    }
+
+    let mut escaped = String::with_capacity(input.len() * 2);
+    for c in input.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // `printf %b` takes octal escapes of bytes
+            c if c.is_control() => {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    escaped.push_str(&format!("\\0{:03o}", byte));
                 }
             }
+            c => escaped.push(c),
         }
+    }
+    format!("printf '%b' {} | codec {}", shell_quote(&escaped), command)
+}
 
-        Ok(())
+/// A single JSON document: `{"codecs": [...]}`, one object per codec with its
+/// `name`, `description`, `modes`, `invertible`, `feature`, `options` (`null`
+/// if not declared) and `examples`.
+fn write_json(
+    output: &mut dyn std::io::Write,
+    codecs: &[(&str, &dyn Codec)],
+) -> anyhow::Result<()> {
+    let codecs: Vec<String> = codecs
+        .iter()
+        .map(|(name, codec)| {
+            let modes: Vec<String> = codec
+                .modes()
+                .iter()
+                .map(|mode| json_string(&mode.to_string()))
+                .collect();
+            let options = match codec.options() {
+                Some(options) => {
+                    let options: Vec<String> = options.iter().map(json_option).collect();
+                    format!("[{}]", options.join(","))
+                }
+                None => "null".to_string(),
+            };
+            let examples: Vec<String> = codec
+                .examples()
+                .iter()
+                .map(|example| {
+                    format!(
+                        "{{\"command\":{},\"input\":{},\"output\":{}}}",
+                        json_string(example.command),
+                        json_string(example.input),
+                        json_string(example.output)
+                    )
+                })
+                .collect();
+
+            format!(
+                "{{\"name\":{},\"description\":{},\"modes\":[{}],\"invertible\":{},\"feature\":{},\"options\":{},\"examples\":[{}]}}",
+                json_string(name),
+                json_string(&description(*codec)),
+                modes.join(","),
+                codec.is_invertible(),
+                json_optional(codec.feature()),
+                options,
+                examples.join(",")
+            )
+        })
+        .collect();
+
+    writeln!(output, "{{\"codecs\":[{}]}}", codecs.join(","))?;
+    Ok(())
+}
+
+/// An option as a JSON object, e.g. `{"name":"L","long":"level","kind":"text",
+/// "value_name":"level","value_type":"int","values":null,"min":0,"max":9,
+/// "required":false,"default":"6","sensitive":false,"description":"compress
+/// level"}`.
+fn json_option(spec: &OptionSpec) -> String {
+    let (kind, value_name, value_type, values, (min, max)) = match &spec.kind {
+        OptionKind::Switch => ("switch", None, None, None, (None, None)),
+        OptionKind::Text {
+            value_name,
+            value_type,
+        } => match value_type {
+            ValueType::Bytes => ("text", Some(*value_name), Some("bytes"), None, (None, None)),
+            ValueType::Int { min, max } => {
+                ("text", Some(*value_name), Some("int"), None, (*min, *max))
+            }
+            ValueType::Enum(values) => (
+                "text",
+                Some(*value_name),
+                Some("enum"),
+                Some(values),
+                (None, None),
+            ),
+        },
+        OptionKind::Chain => ("chain", None, None, None, (None, None)),
+    };
+    let json_int =
+        |value: Option<i64>| value.map_or_else(|| "null".to_string(), |value| value.to_string());
+    let values = match values {
+        Some(values) => {
+            let values: Vec<String> = values.iter().map(|value| json_string(value)).collect();
+            format!("[{}]", values.join(","))
+        }
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"name\":{},\"long\":{},\"kind\":{},\"value_name\":{},\"value_type\":{},\"values\":{},\"min\":{},\"max\":{},\"required\":{},\"default\":{},\"sensitive\":{},\"description\":{}}}",
+        json_string(&spec.name),
+        json_optional(spec.long),
+        json_string(kind),
+        json_optional(value_name),
+        json_optional(value_type),
+        values,
+        json_int(min),
+        json_int(max),
+        spec.required,
+        json_optional(spec.default),
+        spec.sensitive,
+        json_string(&spec.description)
+    )
+}
+
+fn json_optional(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".to_string(), json_string)
+}
+
+/// A `####` section per codec, so that it can be included in a document (e.g.
+/// the README), with a table of its options.
+fn write_markdown(
+    output: &mut dyn std::io::Write,
+    codecs: &[(&str, &dyn Codec)],
+) -> anyhow::Result<()> {
+    for (index, (name, codec)) in codecs.iter().enumerate() {
+        if index > 0 {
+            writeln!(output)?;
+        }
+        writeln!(output, "#### `{}`", name)?;
+        writeln!(output)?;
+
+        let description = description(*codec);
+        if description.contains('\n') {
+            writeln!(output, "```text\n{}\n```", description)?;
+            writeln!(output)?;
+        } else if !description.is_empty() {
+            writeln!(output, "{}", description)?;
+            writeln!(output)?;
+        }
+
+        write!(output, "Modes: {}.", modes(*codec))?;
+        if let Some(feature) = codec.feature() {
+            write!(output, " Feature: `{}`.", feature)?;
+        }
+        writeln!(output)?;
+
+        let options = codec.options().unwrap_or_default();
+        if !options.is_empty() {
+            writeln!(output)?;
+            writeln!(output, "| Option | Value | Description |")?;
+            writeln!(output, "| --- | --- | --- |")?;
+        }
+        for spec in &options {
+            let mut names = format!("`-{}`", spec.name);
+            if let Some(long) = spec.long {
+                names.push_str(&format!(", `--{}`", long));
+            }
+            let value = match &spec.kind {
+                OptionKind::Switch => String::new(),
+                OptionKind::Text { value_name, .. } => format!("`{}`", value_name),
+                OptionKind::Chain => "`[codecs]`".to_string(),
+            };
+            let mut description = spec.description.to_string();
+            let annotations = spec.annotations();
+            if !annotations.is_empty() {
+                description.push_str(&format!(" ({})", annotations.join(", ")));
+            }
+            writeln!(
+                output,
+                "| {} | {} | {} |",
+                names,
+                value,
+                markdown_cell(&description)
+            )?;
+        }
+
+        let examples = codec.examples();
+        if !examples.is_empty() {
+            writeln!(output)?;
+            writeln!(output, "Examples:")?;
+            writeln!(output)?;
+            writeln!(output, "```")?;
+            for example in &examples {
+                writeln!(
+                    output,
+                    "$ {}",
+                    example_command_line(example.command, example.input)
+                )?;
+                writeln!(output, "{}", example.output)?;
+            }
+            writeln!(output, "```")?;
+        }
     }
+    Ok(())
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// A man page (roff), `codec(1)`, with a subsection per codec.
+fn write_man(output: &mut dyn std::io::Write, codecs: &[(&str, &dyn Codec)]) -> anyhow::Result<()> {
+    writeln!(output, ".TH CODEC 1")?;
+    writeln!(output, ".SH NAME")?;
+    writeln!(
+        output,
+        "codec \\- a Swiss Army knife for en/de\\- coding/crypting strings"
+    )?;
+    writeln!(output, ".SH SYNOPSIS")?;
+    writeln!(output, ".B codec")?;
+    writeln!(output, "[\\fIoptions\\fR] [\\fIcodecs\\fR]")?;
+    writeln!(output, ".SH CODECS")?;
+
+    for (name, codec) in codecs {
+        writeln!(output, ".SS {}", roff(name))?;
+        let description = description(*codec);
+        if !description.is_empty() {
+            writeln!(output, ".nf")?;
+            writeln!(output, "{}", roff(&description))?;
+            writeln!(output, ".fi")?;
+        }
+
+        let mut modes = format!("Modes: {}.", modes(*codec));
+        if let Some(feature) = codec.feature() {
+            modes.push_str(&format!(" Feature: {}.", feature));
+        }
+        writeln!(output, ".PP")?;
+        writeln!(output, "{}", roff(&modes))?;
+
+        for spec in codec.options().unwrap_or_default() {
+            let mut names = format!("-{}", spec.name);
+            if let Some(long) = spec.long {
+                names.push_str(&format!(", --{}", long));
+            }
+            let value = match &spec.kind {
+                OptionKind::Switch => String::new(),
+                OptionKind::Text { value_name, .. } => format!(" \\fI{}\\fR", roff(value_name)),
+                OptionKind::Chain => " [\\fIcodecs\\fR]".to_string(),
+            };
+            let mut description = spec.description.to_string();
+            let annotations = spec.annotations();
+            if !annotations.is_empty() {
+                description.push_str(&format!(" ({})", annotations.join(", ")));
+            }
+            writeln!(output, ".TP")?;
+            writeln!(output, "\\fB{}\\fR{}", roff(&names), value)?;
+            writeln!(output, "{}", roff(&description))?;
+        }
+
+        let examples = codec.examples();
+        if !examples.is_empty() {
+            writeln!(output, ".PP")?;
+            writeln!(output, "Examples:")?;
+            writeln!(output, ".PP")?;
+            writeln!(output, ".RS")?;
+            writeln!(output, ".nf")?;
+            for example in &examples {
+                let command_line = example_command_line(example.command, example.input);
+                writeln!(output, "$ {}", roff(&command_line))?;
+                writeln!(output, "{}", roff(example.output))?;
+            }
+            writeln!(output, ".fi")?;
+            writeln!(output, ".RE")?;
+        }
+    }
+    Ok(())
+}
+
+/// Escapes `text` for roff: backslashes, dashes, and lines starting with a
+/// control character.
+fn roff(text: &str) -> String {
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line
+            }
+        })
+        .collect();
+    lines.join("\n")
 }
@@ -0,0 +1,88 @@
+use crate::codecs::{
+    CodecMetaInfo, CodecMode, CodecUsage, Example, MetaCodec, OptionSpec, Options,
+};
+
+#[derive(Default)]
+pub struct VerifyRoundtripMetaCodec;
+
+impl MetaCodec for VerifyRoundtripMetaCodec {
+    fn run_meta_codec(
+        &self,
+        input: &mut (dyn std::io::Read + Send),
+        global_mode: CodecMode,
+        options: &Options,
+        _codec_meta_info: &CodecMetaInfo,
+        output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let chain = options.get_chain("C").ok_or_else(|| {
+            anyhow::anyhow!("verify-roundtrip: missing required option chain (-C)")
+        })?;
+        let inverse = chain.reverse(global_mode)?;
+
+        let mut buffer = Vec::<u8>::with_capacity(1024 * 8);
+        std::io::copy(input, &mut buffer)?;
+
+        let mut result = Vec::with_capacity(buffer.len());
+        chain.run(&mut buffer.as_slice(), global_mode, &mut result)?;
+
+        let mut roundtrip = Vec::with_capacity(buffer.len());
+        inverse.run(&mut result.as_slice(), global_mode, &mut roundtrip)?;
+
+        if let Some(offset) = first_mismatch(&buffer, &roundtrip) {
+            anyhow::bail!(
+                "verify-roundtrip: mismatch at offset {}: expect {}, found {}",
+                offset,
+                describe_byte(buffer.get(offset)),
+                describe_byte(roundtrip.get(offset)),
+            );
+        }
+
+        output.write_all(&result)?;
+        Ok(())
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::chain("C", "the chain to verify")
+            .long("chain")
+            .required()])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new(
+            "verify-roundtrip -C [hex base64]",
+            "hello",
+            "Njg2NTZjNmM2Zg==",
+        )]
+    }
+}
+
+impl CodecUsage for VerifyRoundtripMetaCodec {
+    fn usage(&self) -> String {
+        "    run the chain on input, then run its inverse (see `-r`) on the result, and fail
+    with the offset of the first mismatch if it is not the same as input. output the
+    result of the chain if they match, e.g.:
+    verify-roundtrip -C [zlib aes-cbc -K key -IV iv base64]
+"
+        .to_string()
+    }
+}
+
+/// Offset of the first byte that differs, or of the end of the shorter one.
+fn first_mismatch(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    match expected.iter().zip(actual).position(|(a, b)| a != b) {
+        Some(offset) => Some(offset),
+        None if expected.len() != actual.len() => Some(expected.len().min(actual.len())),
+        None => None,
+    }
+}
+
+fn describe_byte(byte: Option<&u8>) -> String {
+    match byte {
+        Some(byte) => format!("0x{:02x}", byte),
+        None => "end of data".to_string(),
+    }
+}
@@ -1,5 +1,9 @@
 pub mod builtins;
 pub mod meta;
+pub mod plugin;
+mod schema;
+
+pub use self::schema::*;
 
 use std::{
     collections::{btree_map::Iter, BTreeMap, HashMap},
@@ -10,16 +14,36 @@ use std::{
 
 use once_cell::sync::OnceCell;
 
-#[derive(Copy, Clone)]
+use self::{
+    builtins::*,
+    meta::{IfMetaCodec, MapMetaCodec, UsageMetaCodec, VerifyRoundtripMetaCodec},
+};
+use crate::executor::{Chain, Engine};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum CodecMode {
+    #[default]
     Encoding,
     Decoding,
 }
 
+impl CodecMode {
+    pub const ALL: &'static [CodecMode] = &[CodecMode::Encoding, CodecMode::Decoding];
+}
+
+impl std::fmt::Display for CodecMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodecMode::Encoding => write!(f, "encode"),
+            CodecMode::Decoding => write!(f, "decode"),
+        }
+    }
+}
+
 pub trait Codec {
     fn run_codec(
         &self,
-        input: &mut dyn Read,
+        input: &mut (dyn Read + Send),
         global_mode: CodecMode,
         options: &Options,
         output: &mut dyn Write,
@@ -28,9 +52,45 @@ pub trait Codec {
     fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
         None
     }
+
+    /// Options accepted by the codec (besides `-e`/`-d`). Options of a chain
+    /// are checked against them before running it, unless `None` is returned.
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        None
+    }
+
+    /// Whether running the codec in the opposite mode undoes it, so that it
+    /// can be part of a reversed chain (`-r`).
+    fn is_invertible(&self) -> bool {
+        false
+    }
+
+    /// The chain this codec is a shorthand for, given its `options`, e.g.
+    /// `tee -c` for `sink`. Shown by `--explain`.
+    fn expansion(&self, _options: &Options) -> Option<String> {
+        None
+    }
+
+    /// Modes the codec can run in. Codecs ignoring the mode (e.g. `const`)
+    /// run in both.
+    fn modes(&self) -> &'static [CodecMode] {
+        CodecMode::ALL
+    }
+
+    /// Cargo feature the codec is built with, `None` if always built.
+    fn feature(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Example invocations of the codec, shown by `usage`.
+    fn examples(&self) -> Vec<Example> {
+        vec![]
+    }
 }
 
 pub trait CodecUsage {
+    /// Description shown by `usage`, before the lines generated from
+    /// [`Codec::options`].
     fn usage(&self) -> String;
 }
 
@@ -45,10 +105,11 @@ impl CodecMetaInfo {
         }
     }
 
+    /// The global registry used when running codecs, initialized with
+    /// [`load_builtins`] if [`set_instance`](CodecMetaInfo::set_instance) has
+    /// not been called before.
     pub fn instance() -> &'static CodecMetaInfo {
-        GLOBAL_CODEC_META_INFO
-            .get()
-            .expect("GLOBAL_CODEC_META_INFO should have been initialized")
+        GLOBAL_CODEC_META_INFO.get_or_init(load_builtins)
     }
 
     pub fn set_instance(self) {
@@ -79,26 +140,95 @@ impl CodecMetaInfo {
     }
 }
 
+impl Default for CodecMetaInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 static GLOBAL_CODEC_META_INFO: OnceCell<CodecMetaInfo> = OnceCell::new();
 
+/// Registry with all codecs shipped with this crate (depending on enabled features).
+pub fn load_builtins() -> CodecMetaInfo {
+    let mut meta_info = CodecMetaInfo::new();
+    meta_info.register_codec::<IdCodecs>("id");
+    meta_info.register_codec::<ConstCodecs>("const");
+    meta_info.register_codec::<RepeatCodecs>("repeat");
+    meta_info.register_codec::<AppendCodecs>("append");
+    meta_info.register_codec::<NewLineCodecs>("newline");
+    meta_info.register_codec::<CatCodecs>("cat");
+    meta_info.register_codec::<DropCodecs>("drop");
+    meta_info.register_codec::<TakeCodecs>("take");
+    meta_info.register_codec::<TeeCodecs>("tee");
+    meta_info.register_codec::<SinkCodecs>("sink");
+    meta_info.register_codec::<RedirectCodecs>("redirect");
+    meta_info.register_codec::<Base32Codec>("base32");
+    meta_info.register_codec::<Base64Codec>("base64");
+    meta_info.register_codec::<Base85Codec>("base85");
+    meta_info.register("base58", BaseXCodec::new_base58());
+    #[cfg(feature = "libc")]
+    meta_info.register("base58check", BaseXCodec::new_base58check());
+    meta_info.register("base62", BaseXCodec::new_base62());
+    meta_info.register("base36", BaseXCodec::new_base36());
+    meta_info.register_codec::<HexCodec>("hex");
+    #[cfg(feature = "libc")]
+    meta_info.register("aes-cbc", AesCodec::new_cbc());
+    #[cfg(feature = "libc")]
+    meta_info.register("aes-ecb", AesCodec::new_ecb());
+    #[cfg(feature = "libc")]
+    meta_info.register("sm4-cbc", Sm4Codec::new_cbc());
+    #[cfg(feature = "libc")]
+    meta_info.register("sm4-ecb", Sm4Codec::new_ecb());
+    meta_info.register("md5", HashCodec::new_md5());
+    #[cfg(feature = "libc")]
+    meta_info.register("sha256", HashCodec::new_sha256());
+    meta_info.register("sm3", HashCodec::new_sm3());
+    meta_info.register_codec::<UrlCodec>("url");
+    #[cfg(feature = "libc")]
+    meta_info.register_codec::<ZlibCodec>("zlib");
+    meta_info.register_codec::<EscapeCodec>("escape");
+    #[cfg(feature = "libc")]
+    meta_info.register_codec::<RsaCryptCodec>("rsa-crypt");
+    #[cfg(feature = "libc")]
+    meta_info.register_codec::<RsaSignCodec>("rsa-sign");
+
+    #[cfg(feature = "system")]
+    meta_info.register_codec::<SystemCodec>("system");
+
+    meta_info.register_meta("if", IfMetaCodec);
+    meta_info.register_meta("map", MapMetaCodec);
+    meta_info.register_meta("usage", UsageMetaCodec);
+    meta_info.register_meta("verify-roundtrip", VerifyRoundtripMetaCodec);
+    meta_info
+}
+
 pub struct Options {
     options: HashMap<String, Option<Vec<u8>>>,
+    chains: HashMap<String, Chain>,
+    /// The engine of the chain the codec runs in.
+    engine: Engine,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Options {
     pub fn new() -> Self {
         Options {
             options: HashMap::new(),
+            chains: HashMap::new(),
+            engine: Engine::default(),
         }
     }
 
     pub fn insert_switch(&mut self, name: &str) {
-        debug_assert!(!name.chars().next().unwrap().is_uppercase());
         self.options.insert(name.to_string(), None);
     }
 
     pub fn insert_text(&mut self, name: &str, value: &[u8]) {
-        debug_assert!(name.chars().next().unwrap().is_uppercase());
         self.options.insert(name.to_string(), Some(value.to_vec()));
     }
 
@@ -107,13 +237,12 @@ impl Options {
     }
 
     pub fn get_switch(&self, name: &str) -> bool {
-        debug_assert!(!name.chars().next().unwrap().is_uppercase());
         self.options.contains_key(name)
     }
 
+    /// The value of a text option, `None` if it is not given or is a switch.
     pub fn get_text_raw(&self, name: &str) -> Option<&[u8]> {
-        debug_assert!(name.chars().next().unwrap().is_uppercase());
-        Some(self.options.get(name)?.as_ref().unwrap())
+        self.options.get(name)?.as_deref()
     }
 
     pub fn get_text_str(&self, name: &str) -> anyhow::Result<Option<&str>> {
@@ -130,27 +259,90 @@ impl Options {
     {
         Ok(self.get_text_str(name)?.map(str::parse).transpose()?)
     }
+
+    /// All switch (`None`) and text options, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&[u8]>)> {
+        let mut options: Vec<_> = self
+            .options
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+            .collect();
+        options.sort_by_key(|(name, _)| *name);
+        options.into_iter()
+    }
+
+    pub fn insert_chain(&mut self, name: &str, chain: Chain) {
+        self.chains.insert(name.to_string(), chain);
+    }
+
+    pub fn get_chain(&self, name: &str) -> Option<&Chain> {
+        self.chains.get(name)
+    }
+
+    pub(crate) fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+    }
+
+    /// The engine of the chain the codec runs in, to run a chain of its own
+    /// (e.g. an alias) like its chain options.
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
 }
 
 pub trait MetaCodec {
     fn run_meta_codec(
         &self,
-        input: &mut dyn Read,
+        input: &mut (dyn Read + Send),
         global_mode: CodecMode,
         options: &Options,
         codec_meta_info: &CodecMetaInfo,
         output: &mut dyn Write,
     ) -> anyhow::Result<()>;
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        None
+    }
+
+    /// See [`Codec::options`].
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        None
+    }
+
+    /// See [`Codec::is_invertible`].
+    fn is_invertible(&self) -> bool {
+        false
+    }
+
+    /// See [`Codec::expansion`].
+    fn expansion(&self, _options: &Options) -> Option<String> {
+        None
+    }
+
+    /// See [`Codec::modes`].
+    fn modes(&self) -> &'static [CodecMode] {
+        CodecMode::ALL
+    }
+
+    /// See [`Codec::feature`].
+    fn feature(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// See [`Codec::examples`].
+    fn examples(&self) -> Vec<Example> {
+        vec![]
+    }
 }
 
 struct MetaCodecWrapper<T: ?Sized> {
     meta: T,
 }
 
-impl<'a, 'i: 'a, T: MetaCodec + ?Sized> Codec for MetaCodecWrapper<T> {
+impl<T: MetaCodec + ?Sized> Codec for MetaCodecWrapper<T> {
     fn run_codec(
         &self,
-        input: &mut dyn Read,
+        input: &mut (dyn Read + Send),
         global_mode: CodecMode,
         options: &Options,
         output: &mut dyn Write,
@@ -163,4 +355,32 @@ impl<'a, 'i: 'a, T: MetaCodec + ?Sized> Codec for MetaCodecWrapper<T> {
             output,
         )
     }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        self.meta.as_codec_usage()
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        self.meta.options()
+    }
+
+    fn is_invertible(&self) -> bool {
+        self.meta.is_invertible()
+    }
+
+    fn expansion(&self, options: &Options) -> Option<String> {
+        self.meta.expansion(options)
+    }
+
+    fn modes(&self) -> &'static [CodecMode] {
+        self.meta.modes()
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        self.meta.feature()
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        self.meta.examples()
+    }
 }
@@ -0,0 +1,251 @@
+//! External codecs implemented as executables named `codec-<name>`.
+//!
+//! Plugins are searched in the directories of `$CODEC_PLUGIN_PATH` (or
+//! `~/.config/codec/plugins` if not set), then in `$PATH`. The first
+//! executable found for a name wins, and codecs that are already registered
+//! (builtins, aliases) are never replaced.
+//!
+//! Protocol:
+//! - the plugin is run as `codec-<name> encode|decode [options]`, where options
+//!   are passed as given to the codec, sorted by name: `-x` for switches and
+//!   `-K value` (two arguments) for text options. Values are passed as raw
+//!   bytes on unix, and must be valid UTF-8 elsewhere. The mode switches `-e`
+//!   and `-d` are not passed, they only choose `encode` or `decode`
+//! - the environment variables `CODEC_MODE` (`encode` or `decode`) and
+//!   `CODEC_PLUGIN_NAME` (`<name>`) are set
+//! - input is piped to stdin, and stdout is used as output. stderr is inherited
+//! - a non-zero exit status is an error
+//! - `codec-<name> --codec-usage` prints the usage text (the lines shown by
+//!   `codec -H <name>`) and exits with 0
+//!
+//! Plugins don't declare their options, so the options given to a plugin are
+//! parsed by their names: lower case ones are switches and upper case ones take
+//! the next argument, unless the value is given inline (`-k=value`). E.g. in
+//! `codec rev -k x`, `x` is the next codec, not the value of `-k`.
+
+use std::{
+    ffi::OsString,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+};
+
+use crate::codecs::{Codec, CodecMetaInfo, CodecMode, CodecUsage, Options};
+
+const PLUGIN_PREFIX: &str = "codec-";
+const USAGE_ARG: &str = "--codec-usage";
+const SWITCH_ENCODING: &str = "e";
+const SWITCH_DECODING: &str = "d";
+
+pub struct PluginCodec {
+    name: String,
+    path: PathBuf,
+}
+
+impl PluginCodec {
+    pub fn new(name: &str, path: PathBuf) -> Box<Self> {
+        Box::new(PluginCodec {
+            name: name.to_string(),
+            path,
+        })
+    }
+}
+
+impl Codec for PluginCodec {
+    fn run_codec(
+        &self,
+        input: &mut (dyn std::io::Read + Send),
+        global_mode: CodecMode,
+        options: &Options,
+        output: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        if options.get_switch(SWITCH_ENCODING) && options.get_switch(SWITCH_DECODING) {
+            anyhow::bail!("{}: -e and -d cannot be used together", self.name);
+        }
+        let mode = match global_mode {
+            CodecMode::Encoding => "encode",
+            CodecMode::Decoding => "decode",
+        };
+
+        let mut command = Command::new(&self.path);
+        command
+            .arg(mode)
+            .env("CODEC_MODE", mode)
+            .env("CODEC_PLUGIN_NAME", &self.name);
+
+        for (name, value) in options.iter() {
+            if name == SWITCH_ENCODING || name == SWITCH_DECODING {
+                continue;
+            }
+            command.arg(format!("-{}", name));
+            if let Some(value) = value {
+                command.arg(to_os_string(value)?);
+            }
+        }
+
+        let status = run_plugin(command, input, output)?;
+        if !status.success() {
+            anyhow::bail!("{}: plugin exited with {}", self.name, status);
+        }
+
+        Ok(())
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+}
+
+impl CodecUsage for PluginCodec {
+    fn usage(&self) -> String {
+        let usage = std::process::Command::new(&self.path)
+            .arg(USAGE_ARG)
+            .stderr(std::process::Stdio::inherit())
+            .output();
+
+        let mut usage = match usage {
+            Ok(output) if output.status.success() => {
+                String::from_utf8_lossy(&output.stdout).into_owned()
+            }
+            _ => String::new(),
+        };
+        usage.push_str(&format!("    (plugin: {})\n", self.path.display()));
+        usage.push_str(
+            "    (options are not declared: lower case options are switches, upper case\n    \
+             options take a value, -k=value gives a value inline)\n",
+        );
+        usage
+    }
+}
+
+/// Runs the plugin `command`, writing `input` to its stdin from another thread
+/// while its stdout is copied to `output`.
+fn run_plugin(
+    mut command: Command,
+    input: &mut (dyn Read + Send),
+    output: &mut dyn Write,
+) -> anyhow::Result<ExitStatus> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|err| {
+            let program = command.get_program().to_string_lossy();
+            anyhow::anyhow!("failed to run {}: {}", program, err)
+        })?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("failed to open stdin"))?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("failed to open stdout"))?;
+
+    let copied = std::thread::scope(|scope| {
+        let writer = scope.spawn(move || match std::io::copy(input, &mut stdin) {
+            // the plugin may exit without reading its whole input
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+            result => result.map(|_| ()),
+        });
+
+        let read = std::io::copy(&mut stdout, output).map(|_| ());
+        // unblocks the plugin and the writer if the output failed
+        drop(stdout);
+        let written = writer
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        read.and(written)
+    });
+
+    let status = child.wait()?;
+    copied?;
+    Ok(status)
+}
+
+/// Plugin directories, from `$CODEC_PLUGIN_PATH` or `codec/plugins` in the user
+/// config directory, followed by `$PATH`.
+pub fn default_plugin_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = match std::env::var_os("CODEC_PLUGIN_PATH") {
+        Some(paths) => std::env::split_paths(&paths).collect(),
+        None => std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|config_dir| config_dir.join("codec").join("plugins"))
+            .into_iter()
+            .collect(),
+    };
+
+    if let Some(paths) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&paths));
+    }
+    dirs
+}
+
+/// Registers every `codec-<name>` executable found in `dirs` as codec `<name>`.
+pub fn load_plugins(meta_info: &mut CodecMetaInfo, dirs: &[PathBuf]) {
+    let current_exe = std::env::current_exe()
+        .and_then(std::fs::canonicalize)
+        .ok();
+
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = plugin_name(&path) else {
+                continue;
+            };
+            if meta_info.lookup(name).is_some() || !is_executable(&path) {
+                continue;
+            }
+            // e.g. `codec-rust` itself
+            if current_exe.is_some() && std::fs::canonicalize(&path).ok() == current_exe {
+                continue;
+            }
+
+            meta_info.register(name, PluginCodec::new(name, path.clone()));
+        }
+    }
+}
+
+fn plugin_name(path: &Path) -> Option<&str> {
+    let file_name = if cfg!(windows) {
+        path.file_name()?.to_str()?.strip_suffix(".exe")?
+    } else {
+        path.file_name()?.to_str()?
+    };
+    file_name
+        .strip_prefix(PLUGIN_PREFIX)
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(unix)]
+fn to_os_string(value: &[u8]) -> anyhow::Result<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+
+    Ok(OsString::from_vec(value.to_vec()))
+}
+
+#[cfg(not(unix))]
+fn to_os_string(value: &[u8]) -> anyhow::Result<OsString> {
+    Ok(std::str::from_utf8(value)?.into())
+}
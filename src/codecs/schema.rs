@@ -0,0 +1,258 @@
+use std::borrow::Cow;
+
+/// Declaration of an option accepted by a codec, used to validate the options
+/// of a chain before running it and to generate the usage text.
+#[derive(Clone, Debug)]
+pub struct OptionSpec {
+    pub name: Cow<'static, str>,
+    /// Long name, e.g. `key` for `--key`.
+    pub long: Option<&'static str>,
+    pub kind: OptionKind,
+    pub required: bool,
+    pub default: Option<&'static str>,
+    /// The value is a secret (e.g. a key), hidden by `--explain --redact`.
+    pub sensitive: bool,
+    pub description: Cow<'static, str>,
+}
+
+#[derive(Clone, Debug)]
+pub enum OptionKind {
+    /// `-x`, takes no argument
+    Switch,
+    /// `-X value`
+    Text {
+        value_name: &'static str,
+        value_type: ValueType,
+    },
+    /// `-X [codecs]`, a chain run by the codec itself
+    Chain,
+}
+
+#[derive(Clone, Debug)]
+pub enum ValueType {
+    Bytes,
+    /// An integer, within the inclusive bounds if any
+    Int {
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    Enum(&'static [&'static str]),
+}
+
+impl ValueType {
+    /// A short description of the accepted values, e.g. `int in [0, 9]`.
+    fn describe(&self) -> String {
+        match self {
+            ValueType::Bytes => "bytes".to_string(),
+            ValueType::Int {
+                min: Some(min),
+                max: Some(max),
+            } => format!("int in [{}, {}]", min, max),
+            ValueType::Int {
+                min: Some(min),
+                max: None,
+            } => format!("int >= {}", min),
+            ValueType::Int {
+                min: None,
+                max: Some(max),
+            } => format!("int <= {}", max),
+            ValueType::Int {
+                min: None,
+                max: None,
+            } => "int".to_string(),
+            ValueType::Enum(values) => values.join("|"),
+        }
+    }
+}
+
+impl OptionSpec {
+    pub fn switch(name: &'static str, description: &'static str) -> Self {
+        Self::new(name.into(), OptionKind::Switch, description.into())
+    }
+
+    pub fn text(name: &'static str, value_name: &'static str, description: &'static str) -> Self {
+        Self::new(
+            name.into(),
+            OptionKind::Text {
+                value_name,
+                value_type: ValueType::Bytes,
+            },
+            description.into(),
+        )
+    }
+
+    pub fn chain(name: &'static str, description: &'static str) -> Self {
+        Self::new(name.into(), OptionKind::Chain, description.into())
+    }
+
+    pub fn new(name: Cow<'static, str>, kind: OptionKind, description: Cow<'static, str>) -> Self {
+        OptionSpec {
+            name,
+            long: None,
+            kind,
+            required: false,
+            default: None,
+            sensitive: false,
+            description,
+        }
+    }
+
+    pub fn long(mut self, long: &'static str) -> Self {
+        self.long = Some(long);
+        self
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn sensitive(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
+
+    pub fn int(self) -> Self {
+        self.value_type(ValueType::Int {
+            min: None,
+            max: None,
+        })
+    }
+
+    /// An integer in `[min, max]`.
+    pub fn int_range(self, min: i64, max: i64) -> Self {
+        self.value_type(ValueType::Int {
+            min: Some(min),
+            max: Some(max),
+        })
+    }
+
+    /// An integer not less than `min`.
+    pub fn int_at_least(self, min: i64) -> Self {
+        self.value_type(ValueType::Int {
+            min: Some(min),
+            max: None,
+        })
+    }
+
+    pub fn one_of(self, values: &'static [&'static str]) -> Self {
+        self.value_type(ValueType::Enum(values))
+    }
+
+    pub fn with_default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    fn value_type(mut self, value_type: ValueType) -> Self {
+        match &mut self.kind {
+            OptionKind::Text {
+                value_type: current,
+                ..
+            } => *current = value_type,
+            _ => panic!("value type of non-text option {}", self.name),
+        }
+        self
+    }
+
+    /// Checks a (evaluated) value of a text option against its type.
+    pub fn check_value(&self, value: &[u8]) -> Result<(), String> {
+        let OptionKind::Text { value_type, .. } = &self.kind else {
+            return Ok(());
+        };
+        match value_type {
+            ValueType::Bytes => Ok(()),
+            ValueType::Int { min, max } => std::str::from_utf8(value)
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .filter(|value| min.is_none_or(|min| *value >= min))
+                .filter(|value| max.is_none_or(|max| *value <= max))
+                .map(|_| ())
+                .ok_or_else(|| {
+                    format!(
+                        "expect {}, found `{}`",
+                        value_type.describe(),
+                        String::from_utf8_lossy(value)
+                    )
+                }),
+            ValueType::Enum(values) => {
+                if values.iter().any(|v| v.as_bytes() == value) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "expect one of {}, found `{}`",
+                        values.join(", "),
+                        String::from_utf8_lossy(value)
+                    ))
+                }
+            }
+        }
+    }
+
+    /// The option line shown in usage, e.g.
+    /// `    -L, --level level: compress level (int in [0, 9], default 6)`
+    pub fn usage(&self) -> String {
+        let mut line = format!("    -{}", self.name);
+        if let Some(long) = self.long {
+            line.push_str(&format!(", --{}", long));
+        }
+
+        match &self.kind {
+            OptionKind::Switch => {}
+            OptionKind::Text { value_name, .. } => {
+                line.push(' ');
+                line.push_str(value_name);
+            }
+            OptionKind::Chain => line.push_str(" [codecs]"),
+        }
+
+        line.push_str(": ");
+        line.push_str(&self.description);
+        let annotations = self.annotations();
+        if !annotations.is_empty() {
+            line.push_str(&format!(" ({})", annotations.join(", ")));
+        }
+        line.push('\n');
+        line
+    }
+
+    /// The type, requirement and default of the option, e.g. `["int",
+    /// "default 6"]`.
+    pub fn annotations(&self) -> Vec<String> {
+        let mut annotations = vec![];
+        if let OptionKind::Text { value_type, .. } = &self.kind {
+            match value_type {
+                ValueType::Bytes => {}
+                ValueType::Int { .. } | ValueType::Enum(_) => {
+                    annotations.push(value_type.describe())
+                }
+            }
+        }
+        if self.required {
+            annotations.push("required".to_string());
+        }
+        if let Some(default) = self.default {
+            annotations.push(format!("default {}", default));
+        }
+        annotations
+    }
+}
+
+/// An example invocation of a codec: `command` (a chain, parsed like the
+/// command line) turns `input` into `output`.
+#[derive(Clone, Debug)]
+pub struct Example {
+    pub command: &'static str,
+    pub input: &'static str,
+    pub output: &'static str,
+}
+
+impl Example {
+    pub fn new(command: &'static str, input: &'static str, output: &'static str) -> Self {
+        Example {
+            command,
+            input,
+            output,
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Shell completion scripts (`codec --completions bash|zsh|fish`), generated
+//! from the codecs of a [`CodecMetaInfo`] and their declared options.
+//!
+//! The scripts complete codec names, the options of the last codec written
+//! (or the global options before the first codec), and file paths after
+//! options taking a `file` (e.g. `-F`, `-O` and `cat -F`).
+
+use std::{io::Write, str::FromStr};
+
+use crate::{
+    codecs::{CodecMetaInfo, OptionKind, OptionSpec},
+    executor::mode_options,
+    utils::shell_quote,
+};
+
+/// Names of the commands completed by the scripts.
+const COMMANDS: &[&str] = &["codec", "codec-rust"];
+const FUNCTION: &str = "_codec";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub const NAMES: &'static [&'static str] = &["bash", "zsh", "fish"];
+}
+
+impl FromStr for Shell {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => anyhow::bail!(
+                "unknown shell: {}, expect one of {}",
+                s,
+                Shell::NAMES.join(", ")
+            ),
+        }
+    }
+}
+
+/// The options of a codec, or the global options if `name` is empty.
+struct CodecOptions {
+    name: String,
+    options: Vec<OptionSpec>,
+}
+
+impl CodecOptions {
+    /// `-X` and `--long` names of all options.
+    fn names(&self) -> Vec<String> {
+        self.options.iter().flat_map(option_names).collect()
+    }
+
+    /// `-X` and `--long` names of the options taking a file.
+    fn file_names(&self) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|spec| takes_file(spec))
+            .flat_map(option_names)
+            .collect()
+    }
+}
+
+fn option_names(spec: &OptionSpec) -> impl Iterator<Item = String> {
+    std::iter::once(format!("-{}", spec.name)).chain(spec.long.map(|long| format!("--{}", long)))
+}
+
+fn takes_file(spec: &OptionSpec) -> bool {
+    matches!(
+        spec.kind,
+        OptionKind::Text {
+            value_name: "file",
+            ..
+        }
+    )
+}
+
+/// Writes the completion script of `shell` for the codecs of `meta_info`, and
+/// `global_options` before the first codec.
+pub fn write_completions(
+    output: &mut dyn Write,
+    shell: Shell,
+    meta_info: &CodecMetaInfo,
+    global_options: Vec<OptionSpec>,
+) -> anyhow::Result<()> {
+    let mut codecs: Vec<CodecOptions> = meta_info
+        .codecs_iter()
+        .map(|(name, codec)| {
+            let mut options = codec.options().unwrap_or_default();
+            options.extend(mode_options());
+            CodecOptions {
+                name: name.clone(),
+                options,
+            }
+        })
+        .collect();
+    codecs.sort_by(|a, b| a.name.cmp(&b.name));
+    let global = CodecOptions {
+        name: String::new(),
+        options: global_options,
+    };
+
+    match shell {
+        Shell::Bash => write_bash(output, &global, &codecs),
+        Shell::Zsh => write_zsh(output, &global, &codecs),
+        Shell::Fish => write_fish(output, &global, &codecs),
+    }
+}
+
+fn write_bash(
+    output: &mut dyn Write,
+    global: &CodecOptions,
+    codecs: &[CodecOptions],
+) -> anyhow::Result<()> {
+    let names: Vec<&str> = codecs.iter().map(|codec| codec.name.as_str()).collect();
+
+    writeln!(output, "{}() {{", FUNCTION)?;
+    writeln!(output, "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+    writeln!(output, "    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+    writeln!(output, "    local codec=\"\" word i")?;
+    writeln!(output, "    for ((i = 1; i < COMP_CWORD; i++)); do")?;
+    writeln!(output, "        word=\"${{COMP_WORDS[i]#[}}\"")?;
+    writeln!(output, "        case \"$word\" in")?;
+    writeln!(
+        output,
+        "            {}) codec=\"$word\" ;;",
+        case_pattern(&names)
+    )?;
+    writeln!(output, "        esac")?;
+    writeln!(output, "    done")?;
+    writeln!(output)?;
+
+    let file_options = file_options(global, codecs);
+    if !file_options.is_empty() {
+        let file_options: Vec<&str> = file_options.iter().map(String::as_str).collect();
+        writeln!(output, "    case \"$codec:$prev\" in")?;
+        writeln!(output, "        {})", case_pattern(&file_options))?;
+        writeln!(output, "            COMPREPLY=($(compgen -f -- \"$cur\"))")?;
+        writeln!(output, "            return ;;")?;
+        writeln!(output, "    esac")?;
+        writeln!(output)?;
+    }
+
+    writeln!(output, "    if [[ \"$cur\" != -* ]]; then")?;
+    writeln!(
+        output,
+        "        COMPREPLY=($(compgen -W {} -- \"$cur\"))",
+        shell_quote(&names.join(" "))
+    )?;
+    writeln!(output, "        return")?;
+    writeln!(output, "    fi")?;
+    writeln!(output, "    case \"$codec\" in")?;
+    for codec in std::iter::once(global).chain(codecs) {
+        writeln!(
+            output,
+            "        {}) COMPREPLY=($(compgen -W {} -- \"$cur\")) ;;",
+            shell_quote(&codec.name),
+            shell_quote(&codec.names().join(" "))
+        )?;
+    }
+    writeln!(output, "    esac")?;
+    writeln!(output, "}}")?;
+    writeln!(output, "complete -F {} {}", FUNCTION, COMMANDS.join(" "))?;
+    Ok(())
+}
+
+fn write_zsh(
+    output: &mut dyn Write,
+    global: &CodecOptions,
+    codecs: &[CodecOptions],
+) -> anyhow::Result<()> {
+    let names: Vec<&str> = codecs.iter().map(|codec| codec.name.as_str()).collect();
+
+    writeln!(output, "#compdef {}", COMMANDS.join(" "))?;
+    writeln!(output)?;
+    writeln!(output, "{}() {{", FUNCTION)?;
+    writeln!(output, "    local codec=\"\" word i")?;
+    writeln!(output, "    local -a options")?;
+    writeln!(output, "    for ((i = 2; i < CURRENT; i++)); do")?;
+    writeln!(output, "        word=\"${{words[i]#\\[}}\"")?;
+    writeln!(output, "        case \"$word\" in")?;
+    writeln!(
+        output,
+        "            ({}) codec=\"$word\" ;;",
+        case_pattern(&names)
+    )?;
+    writeln!(output, "        esac")?;
+    writeln!(output, "    done")?;
+    writeln!(output)?;
+
+    let file_options = file_options(global, codecs);
+    if !file_options.is_empty() {
+        let file_options: Vec<&str> = file_options.iter().map(String::as_str).collect();
+        writeln!(output, "    case \"$codec:${{words[CURRENT-1]}}\" in")?;
+        writeln!(output, "        ({})", case_pattern(&file_options))?;
+        writeln!(output, "            _files")?;
+        writeln!(output, "            return ;;")?;
+        writeln!(output, "    esac")?;
+        writeln!(output)?;
+    }
+
+    writeln!(output, "    if [[ \"$PREFIX\" != -* ]]; then")?;
+    writeln!(
+        output,
+        "        compadd -- {}",
+        names
+            .iter()
+            .map(|name| shell_quote(name))
+            .collect::<Vec<_>>()
+            .join(" ")
+    )?;
+    writeln!(output, "        return")?;
+    writeln!(output, "    fi")?;
+    writeln!(output, "    case \"$codec\" in")?;
+    for codec in std::iter::once(global).chain(codecs) {
+        let options: Vec<String> = codec
+            .options
+            .iter()
+            .flat_map(|spec| {
+                option_names(spec)
+                    .map(|name| shell_quote(&format!("{}:{}", name, one_line(&spec.description))))
+            })
+            .collect();
+        writeln!(
+            output,
+            "        ({}) options=({}) ;;",
+            shell_quote(&codec.name),
+            options.join(" ")
+        )?;
+    }
+    writeln!(output, "    esac")?;
+    writeln!(output, "    _describe -t options option options")?;
+    writeln!(output, "}}")?;
+    writeln!(output)?;
+    writeln!(output, "{} \"$@\"", FUNCTION)?;
+    Ok(())
+}
+
+fn write_fish(
+    output: &mut dyn Write,
+    global: &CodecOptions,
+    codecs: &[CodecOptions],
+) -> anyhow::Result<()> {
+    let names: Vec<&str> = codecs.iter().map(|codec| codec.name.as_str()).collect();
+    let quoted_names: Vec<String> = names.iter().map(|name| shell_quote(name)).collect();
+
+    writeln!(output, "function __codec_is")?;
+    writeln!(output, "    set -l codec \"\"")?;
+    writeln!(output, "    for word in (commandline -opc)[2..-1]")?;
+    writeln!(output, "        set word (string trim -l -c '[' -- $word)")?;
+    writeln!(
+        output,
+        "        if contains -- $word {}",
+        quoted_names.join(" ")
+    )?;
+    writeln!(output, "            set codec $word")?;
+    writeln!(output, "        end")?;
+    writeln!(output, "    end")?;
+    writeln!(output, "    test \"$codec\" = \"$argv[1]\"")?;
+    writeln!(output, "end")?;
+    writeln!(output)?;
+    writeln!(output, "for command in {}", COMMANDS.join(" "))?;
+    writeln!(output, "    complete -c $command -f")?;
+    writeln!(
+        output,
+        "    complete -c $command -a {}",
+        shell_quote(&names.join(" "))
+    )?;
+    for codec in std::iter::once(global).chain(codecs) {
+        let condition = double_quote(&format!("__codec_is {}", shell_quote(&codec.name)));
+        for spec in &codec.options {
+            // `-s` only declares single letter options, `-o` any other one
+            let short = if spec.name.chars().count() == 1 {
+                "-s"
+            } else {
+                "-o"
+            };
+            let mut line = format!(
+                "    complete -c $command -n {} {} {}",
+                condition, short, spec.name
+            );
+            if let Some(long) = spec.long {
+                line.push_str(&format!(" -l {}", long));
+            }
+            if takes_file(spec) {
+                line.push_str(" -r -F");
+            } else if !matches!(spec.kind, OptionKind::Switch) {
+                line.push_str(" -r");
+            }
+            line.push_str(&format!(
+                " -d {}",
+                shell_quote(&one_line(&spec.description))
+            ));
+            writeln!(output, "{}", line)?;
+        }
+    }
+    writeln!(output, "end")?;
+    Ok(())
+}
+
+/// `codec:option` for each option taking a file, matched against the current
+/// codec and the previous word by the bash and zsh scripts.
+fn file_options(global: &CodecOptions, codecs: &[CodecOptions]) -> Vec<String> {
+    std::iter::once(global)
+        .chain(codecs)
+        .flat_map(|codec| {
+            codec
+                .file_names()
+                .into_iter()
+                .map(|option| format!("{}:{}", codec.name, option))
+        })
+        .collect()
+}
+
+/// `description` on a single line, as shown next to the options by zsh and
+/// fish.
+fn one_line(description: &str) -> String {
+    description.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A `case` pattern matching any of `words`, e.g. `'a'|'b'`.
+fn case_pattern(words: &[&str]) -> String {
+    words
+        .iter()
+        .map(|word| shell_quote(word))
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Quotes `word` in double quotes, e.g. `'a'` as `"'a'"`.
+fn double_quote(word: &str) -> String {
+    let mut result = String::from('"');
+    for c in word.chars() {
+        if matches!(c, '"' | '\\' | '$') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result.push('"');
+    result
+}
@@ -1,25 +1,47 @@
-#[derive(Clone, Debug)]
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    codecs::OptionSpec,
+    executor::{parser, Pipeline},
+};
+
+/// A parsed command line: global options and the chain of codecs.
+///
+/// It is printed as the canonical string of the chain, which is parsed back to
+/// the same command by [`parser::parse_str`], e.g.
+/// `-d base64 aes-ecb -K [ 3132 hex -d ] append -A '\]'`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Command {
-    pub(super) options: Vec<CommandOption>,
-    pub(super) codecs: Vec<Codec>,
+    pub(crate) options: Vec<CommandOption>,
+    pub(crate) codecs: Vec<Codec>,
 }
 
-#[derive(Clone, Debug)]
-pub(super) struct Codec {
-    pub(super) name: String,
-    pub(super) options: Vec<CommandOption>,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Codec {
+    pub(crate) name: String,
+    pub(crate) options: Vec<CommandOption>,
 }
 
-#[derive(Clone, Debug)]
-pub(super) enum CommandOption {
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CommandOption {
     Switch(String),
     Value { name: String, text: Text },
 }
 
-#[derive(Clone, Debug)]
-pub(super) enum Text {
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Text {
     String(String),
+    Bytes(Vec<u8>),
     Codecs { input: String, codecs: Vec<Codec> },
+    Chain(Vec<Codec>),
+}
+
+impl CommandOption {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            CommandOption::Switch(name) | CommandOption::Value { name, .. } => name,
+        }
+    }
 }
 
 impl Default for Text {
@@ -27,3 +49,103 @@ impl Default for Text {
         Text::String(String::new())
     }
 }
+
+impl Display for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let specs = Pipeline::options();
+        for (i, option) in self.options.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            option.write(f, &specs)?;
+        }
+        if !self.options.is_empty() && !self.codecs.is_empty() {
+            write!(f, " ")?;
+        }
+        write_words(f, &self.codecs)
+    }
+}
+
+impl Display for Codec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        let specs = parser::codec_option_specs(&self.name);
+        for option in &self.options {
+            write!(f, " ")?;
+            option.write(f, &specs)?;
+        }
+        Ok(())
+    }
+}
+
+impl CommandOption {
+    /// Writes the option to be parsed back the same way with the option
+    /// `specs` of its codec: a value that does not follow from the name of its
+    /// option is written inline (`-x=value`).
+    fn write(&self, f: &mut Formatter<'_>, specs: &[OptionSpec]) -> std::fmt::Result {
+        match self {
+            CommandOption::Switch(name) => write!(f, "-{}", name),
+            CommandOption::Value { name, text } => {
+                let inline = match text {
+                    _ if parser::takes_next_value(name, specs) => None,
+                    Text::String(value) => Some(value.as_str()),
+                    Text::Bytes(value) => std::str::from_utf8(value).ok(),
+                    Text::Codecs { .. } | Text::Chain(_) => None,
+                };
+                match inline.and_then(|value| parser::format_word(&format!("-{}={}", name, value)))
+                {
+                    Some(word) => write!(f, "{}", word),
+                    None => write!(f, "-{} {}", name, text),
+                }
+            }
+        }
+    }
+}
+
+impl Display for Text {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Text::String(value) => write_value(f, value.as_bytes()),
+            Text::Bytes(value) => write_value(f, value),
+            Text::Codecs { input, codecs } => {
+                // an input that cannot be written as a word goes through hex
+                match parser::format_word(input) {
+                    Some(input) => write!(f, "[ {}", input)?,
+                    None => write!(f, "[ {} hex -d", hex::encode(input))?,
+                }
+                for codec in codecs {
+                    write!(f, " {}", codec)?;
+                }
+                write!(f, " ]")
+            }
+            Text::Chain(codecs) => {
+                write!(f, "[ ")?;
+                for codec in codecs {
+                    write!(f, "{} ", codec)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+fn write_words<T: Display>(f: &mut Formatter<'_>, words: &[T]) -> std::fmt::Result {
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", word)?;
+    }
+    Ok(())
+}
+
+/// Writes a value as is if possible, otherwise as `[ <hex> hex -d ]`.
+fn write_value(f: &mut Formatter<'_>, value: &[u8]) -> std::fmt::Result {
+    match std::str::from_utf8(value)
+        .ok()
+        .and_then(parser::format_word)
+    {
+        Some(word) => write!(f, "{}", word),
+        None => write!(f, "[ {} hex -d ]", hex::encode(value)),
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    io::{BufRead, Read, Write},
+    str::FromStr,
+    sync::mpsc,
+};
+
+/// How the codecs of a chain are connected to each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// One thread per codec, connected by `pipe`, which hands over every
+    /// (possibly tiny) write to the next codec, so output is streamed as soon
+    /// as it is written (e.g. `tail -f log | codec ...`).
+    #[default]
+    Threaded,
+    /// One thread per codec, passing large buffers, reused once read, between
+    /// codecs. A buffer is only passed once full or at the end of the output
+    /// of a codec, so output is not streamed.
+    Buffered,
+    /// All codecs on the current thread, one after another, each one on the
+    /// whole output of the previous one in memory. Codecs write their output
+    /// until their input ends, so nothing is streamed and the memory used
+    /// grows with the data: large inputs are better run by `Buffered`.
+    /// Sub-codec options (`-X [input codecs]`) are always evaluated this way.
+    Sequential,
+}
+
+impl Engine {
+    pub const NAMES: &'static [&'static str] = &["threaded", "buffered", "sequential"];
+}
+
+impl std::fmt::Display for Engine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Engine::Threaded => "threaded",
+            Engine::Buffered => "buffered",
+            Engine::Sequential => "sequential",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Engine {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "threaded" => Ok(Engine::Threaded),
+            "buffered" => Ok(Engine::Buffered),
+            "sequential" => Ok(Engine::Sequential),
+            _ => anyhow::bail!(
+                "unknown engine: {}, expect one of {}",
+                s,
+                Engine::NAMES.join(", ")
+            ),
+        }
+    }
+}
+
+const BUFFER_SIZE: usize = 64 * 1024;
+/// Full buffers that can be queued before the writer blocks.
+const BUFFERS_IN_FLIGHT: usize = 4;
+
+/// A pipe passing `BUFFER_SIZE` buffers from the writer to the reader, which
+/// sends them back to the writer to be reused once consumed.
+pub(super) fn buffer_pipe() -> (BufferReader, BufferWriter) {
+    let (full_sender, full_receiver) = mpsc::sync_channel(BUFFERS_IN_FLIGHT);
+    let (empty_sender, empty_receiver) = mpsc::channel();
+
+    let reader = BufferReader {
+        current: Vec::new(),
+        pos: 0,
+        full: full_receiver,
+        empty: empty_sender,
+    };
+    let writer = BufferWriter {
+        buffer: Vec::with_capacity(BUFFER_SIZE),
+        full: full_sender,
+        empty: empty_receiver,
+    };
+    (reader, writer)
+}
+
+pub(super) struct BufferReader {
+    current: Vec<u8>,
+    pos: usize,
+    full: mpsc::Receiver<Vec<u8>>,
+    empty: mpsc::Sender<Vec<u8>>,
+}
+
+impl Read for BufferReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for BufferReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        while self.pos == self.current.len() {
+            // the writer is dropped after sending everything: end of input
+            let Ok(next) = self.full.recv() else {
+                return Ok(&[]);
+            };
+
+            let mut consumed = std::mem::replace(&mut self.current, next);
+            self.pos = 0;
+            if consumed.capacity() > 0 {
+                consumed.clear();
+                // the writer may already be gone
+                let _ = self.empty.send(consumed);
+            }
+        }
+        Ok(&self.current[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.current.len());
+    }
+}
+
+pub(super) struct BufferWriter {
+    buffer: Vec<u8>,
+    full: mpsc::SyncSender<Vec<u8>>,
+    empty: mpsc::Receiver<Vec<u8>>,
+}
+
+impl BufferWriter {
+    fn send(&mut self) -> std::io::Result<()> {
+        let next = self
+            .empty
+            .try_recv()
+            .unwrap_or_else(|_| Vec::with_capacity(BUFFER_SIZE));
+        let full = std::mem::replace(&mut self.buffer, next);
+        self.full
+            .send(full)
+            .map_err(|_| std::io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+impl Write for BufferWriter {
+    // never writes partially, some writers (e.g. `MultiWriter`) do not expect it
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.send()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.send()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BufferWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Writes the whole buffer on every `write`, as the pipes between codecs do,
+/// for the output of the last codec run by the sequential engine. Some writers
+/// (e.g. `MultiWriter`) do not expect partial writes, that `Stdout` may do.
+pub(super) struct WholeWriter<'a, W: Write + ?Sized>(pub(super) &'a mut W);
+
+impl<W: Write + ?Sized> Write for WholeWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt::{Display, Formatter};
+
+use crate::executor::parser::ParseError;
+
+/// Errors returned when building or running a [`Pipeline`](super::Pipeline).
+#[derive(Debug)]
+pub enum Error {
+    /// The command line could not be parsed.
+    Parse(ParseError),
+    /// A global option that is not supported.
+    UnknownOption(String),
+    /// A codec that is not registered in [`CodecMetaInfo`](crate::CodecMetaInfo).
+    CodecNotFound(String),
+    /// The chain cannot be reversed (`-r`), as it contains a codec that is not
+    /// [invertible](crate::Codec::is_invertible).
+    NotInvertible(String),
+    /// An option of `codec` is unknown, missing or has an invalid value,
+    /// according to the options declared by the codec.
+    InvalidOption {
+        codec: String,
+        option: String,
+        reason: String,
+    },
+    /// A codec in the chain failed. `index` is the position of the codec in
+    /// the resolved chain, starting from 0.
+    Codec {
+        name: String,
+        index: usize,
+        source: anyhow::Error,
+    },
+    /// Reading the input or writing the output failed.
+    Io(std::io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "{}", err),
+            Error::UnknownOption(name) => write!(f, "unknown option: {}", name),
+            Error::CodecNotFound(name) => write!(f, "codec not found: {}", name),
+            Error::NotInvertible(name) => {
+                write!(
+                    f,
+                    "cannot reverse the chain: codec {} is not invertible",
+                    name
+                )
+            }
+            Error::InvalidOption {
+                codec,
+                option,
+                reason,
+            } => write!(f, "codec {}: option -{}: {}", codec, option, reason),
+            Error::Codec {
+                name,
+                index,
+                source,
+            } => write!(f, "codec {} (#{} in chain): {}", name, index + 1, source),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(err) => Some(err),
+            Error::Codec { source: err, .. } => Some(err.as_ref()),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -6,165 +6,561 @@ use std::{
 use anyhow::Result;
 
 use crate::{
-    codecs::{self, CodecMetaInfo},
-    executor::commands,
+    codecs::{self, CodecMetaInfo, OptionKind, OptionSpec},
+    executor::{
+        commands,
+        engine::{buffer_pipe, Engine, WholeWriter},
+        stats::{StageCounters, StageStats},
+        Error, Pipeline,
+    },
 };
 
-#[allow(unused)]
-const OPTION_ENCODING: &str = "e";
-const OPTION_DECODING: &str = "d";
-const OPTION_NEW_LINE: &str = "n";
-const OPTION_INPUT_STRING: &str = "I";
-const OPTION_INPUT_FILE: &str = "F";
-const OPTION_OUTPUT_FILE: &str = "O";
-const OPTION_HELP: &str = "h";
-const OPTION_HELP_CODEC: &str = "H";
-
-pub fn execute(mut command: commands::Command) -> Result<()> {
-    let mut global_mode = codecs::CodecMode::Encoding;
-    for o in &command.options {
-        match o {
-            commands::CommandOption::Switch(name) => match name.as_ref() {
-                OPTION_ENCODING => global_mode = codecs::CodecMode::Encoding,
-                OPTION_DECODING => global_mode = codecs::CodecMode::Decoding,
-                OPTION_NEW_LINE => command.codecs.push(commands::Codec {
-                    name: "newline".to_string(),
-                    options: vec![],
-                }),
-                OPTION_HELP => {
-                    let codec = commands::Codec {
-                        name: "usage".to_string(),
-                        options: vec![],
-                    };
-                    command.codecs = vec![codec];
+const SWITCH_ENCODING: &str = "e";
+const SWITCH_DECODING: &str = "d";
+/// Per-codec `e`/`d` switches, accepted by every codec.
+const MODE_SWITCHES: &[&str] = &[SWITCH_ENCODING, SWITCH_DECODING];
+
+/// Runs `command` with stdin as input and stdout as output.
+pub fn execute(command: commands::Command) -> Result<(), Error> {
+    Pipeline::from_command(command)?.run(std::io::stdin(), &mut std::io::stdout())
+}
+
+/// A codec chain passed unevaluated as an option value (`-X [codecs]`), for
+/// codecs that run sub-chains themselves, e.g. `if`.
+#[derive(Clone, Debug)]
+pub struct Chain {
+    pub(crate) codecs: Vec<commands::Codec>,
+    /// The engine of the chain the option was given in.
+    pub(crate) engine: Engine,
+}
+
+impl Chain {
+    /// A chain run by the default engine.
+    pub(crate) fn new(codecs: Vec<commands::Codec>) -> Self {
+        Chain {
+            codecs,
+            engine: Engine::default(),
+        }
+    }
+
+    /// Sets the engine running the chain, see [`Pipeline::engine`].
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Runs the chain on `input` in `mode`, see [`Pipeline::run`].
+    pub fn run(
+        &self,
+        input: &mut (dyn Read + Send),
+        mode: codecs::CodecMode,
+        output: &mut dyn Write,
+    ) -> Result<(), Error> {
+        check_codecs(CodecMetaInfo::instance(), &self.codecs)?;
+
+        run_codecs(input, &self.codecs, mode, self.engine, output, None)
+    }
+
+    /// Returns the inverse of the chain run in `mode`, see [`Pipeline::reverse`].
+    pub fn reverse(&self, mode: codecs::CodecMode) -> Result<Chain, Error> {
+        Ok(Chain {
+            codecs: reverse_codecs(&self.codecs, mode)?,
+            engine: self.engine,
+        })
+    }
+}
+
+/// The codecs of `codec_list` in reverse order, each one with a `-e`/`-d`
+/// switch for the opposite of the mode it would run in, given the global `mode`.
+pub(super) fn reverse_codecs(
+    codec_list: &[commands::Codec],
+    mode: codecs::CodecMode,
+) -> Result<Vec<commands::Codec>, Error> {
+    let meta_info = CodecMetaInfo::instance();
+    let mut reversed = Vec::with_capacity(codec_list.len());
+
+    for codec in codec_list.iter().rev() {
+        let c = meta_info
+            .lookup(&codec.name)
+            .ok_or_else(|| Error::CodecNotFound(codec.name.clone()))?;
+        if !c.is_invertible() {
+            return Err(Error::NotInvertible(codec.name.clone()));
+        }
+
+        // same precedence as when running the codec: `-d` over `-e`
+        let mode = if has_switch(&codec.options, SWITCH_DECODING) {
+            codecs::CodecMode::Decoding
+        } else if has_switch(&codec.options, SWITCH_ENCODING) {
+            codecs::CodecMode::Encoding
+        } else {
+            mode
+        };
+        let inverse = match mode {
+            codecs::CodecMode::Encoding => SWITCH_DECODING,
+            codecs::CodecMode::Decoding => SWITCH_ENCODING,
+        };
+
+        let mut options: Vec<_> = codec
+            .options
+            .iter()
+            .filter(|option| !MODE_SWITCHES.contains(&option.name()))
+            .cloned()
+            .collect();
+        options.push(commands::CommandOption::Switch(inverse.to_string()));
+
+        reversed.push(commands::Codec {
+            name: codec.name.clone(),
+            options,
+        });
+    }
+
+    Ok(reversed)
+}
+
+/// Specs of the `e`/`d` switches, which every codec accepts without declaring
+/// them.
+pub(crate) fn mode_options() -> [OptionSpec; 2] {
+    [
+        OptionSpec::switch(SWITCH_ENCODING, "encode").long("encode"),
+        OptionSpec::switch(SWITCH_DECODING, "decode").long("decode"),
+    ]
+}
+
+pub(super) fn has_switch(options: &[commands::CommandOption], switch: &str) -> bool {
+    options
+        .iter()
+        .any(|option| matches!(option, commands::CommandOption::Switch(name) if name == switch))
+}
+
+/// Checks, before anything runs, that the codecs of `codec_list` (and of their
+/// sub-chains) exist and are given the options they declare. Values of
+/// sub-codec options (`-X [codecs]`) are checked once they are evaluated.
+pub(super) fn check_codecs(
+    meta_info: &CodecMetaInfo,
+    codec_list: &[commands::Codec],
+) -> Result<(), Error> {
+    for codec in codec_list {
+        let c = meta_info
+            .lookup(&codec.name)
+            .ok_or_else(|| Error::CodecNotFound(codec.name.clone()))?;
+        if let Some(specs) = c.options() {
+            check_options(codec, &specs)?;
+        }
+
+        for option in &codec.options {
+            if let commands::CommandOption::Value {
+                text: commands::Text::Codecs { codecs, .. } | commands::Text::Chain(codecs),
+                ..
+            } = option
+            {
+                check_codecs(meta_info, codecs)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_options(codec: &commands::Codec, specs: &[OptionSpec]) -> Result<(), Error> {
+    let invalid = |option: &str, reason: String| Error::InvalidOption {
+        codec: codec.name.clone(),
+        option: option.to_string(),
+        reason,
+    };
+
+    for option in &codec.options {
+        let name = option.name();
+        if MODE_SWITCHES.contains(&name) {
+            continue;
+        }
+
+        let Some(spec) = specs.iter().find(|spec| spec.name == name) else {
+            let reason = match specs
+                .iter()
+                .find(|spec| spec.name.eq_ignore_ascii_case(name))
+            {
+                Some(spec) => format!("unknown option, did you mean -{}?", spec.name),
+                None => "unknown option".to_string(),
+            };
+            return Err(invalid(name, reason));
+        };
+
+        let text = match option {
+            commands::CommandOption::Switch(_) => None,
+            commands::CommandOption::Value { text, .. } => Some(text),
+        };
+        let checked = match (&spec.kind, text) {
+            (OptionKind::Switch, None) => Ok(()),
+            (OptionKind::Switch, Some(_)) => Err("expect no value".to_string()),
+            (OptionKind::Text { .. }, Some(commands::Text::String(value))) => {
+                spec.check_value(value.as_bytes())
+            }
+            (OptionKind::Text { .. }, Some(commands::Text::Bytes(value))) => {
+                spec.check_value(value)
+            }
+            (OptionKind::Text { .. }, Some(commands::Text::Codecs { .. })) => Ok(()),
+            (OptionKind::Text { .. }, _) => Err("expect a value".to_string()),
+            (OptionKind::Chain, Some(commands::Text::Chain(_))) => Ok(()),
+            (OptionKind::Chain, _) => Err("expect a chain [codecs]".to_string()),
+        };
+        checked.map_err(|reason| invalid(name, reason))?;
+    }
+
+    for spec in specs.iter().filter(|spec| spec.required) {
+        if !codec
+            .options
+            .iter()
+            .any(|option| spec.name == option.name())
+        {
+            return Err(invalid(&spec.name, "missing required option".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs `codec_list` on `input` with `engine`, and writes the output of the
+/// last codec to `output`.
+///
+/// When a codec fails, the error of the failing codec (the first one in the
+/// chain that is not a `BrokenPipe`) is returned. Output that was produced
+/// before the failure has already been written to `output`. If `output` itself
+/// is closed (`BrokenPipe`), the chain stops without an error.
+///
+/// If `stats` is given, the statistics of each codec are pushed to it once the
+/// chain ends, even if it failed.
+pub(super) fn run_codecs<W: Write + ?Sized>(
+    input: &mut (dyn Read + Send),
+    codec_list: &[commands::Codec],
+    mode: codecs::CodecMode,
+    engine: Engine,
+    output: &mut W,
+    stats: Option<&mut Vec<StageStats>>,
+) -> Result<(), Error> {
+    let counters: Option<Vec<StageCounters>> = stats
+        .is_some()
+        .then(|| codec_list.iter().map(|_| Default::default()).collect());
+
+    let result = match engine {
+        Engine::Threaded | Engine::Buffered => {
+            run_codecs_threaded(input, codec_list, mode, engine, output, counters.as_deref())
+        }
+        Engine::Sequential => {
+            run_codecs_sequential(input, codec_list, mode, output, counters.as_deref())
+        }
+    };
+
+    if let (Some(stats), Some(counters)) = (stats, counters) {
+        stats.extend(
+            codec_list
+                .iter()
+                .zip(&counters)
+                .map(|(c, counter)| counter.to_stats(&c.name)),
+        );
+    }
+    result
+}
+
+/// One thread per codec, connected by `pipe` or `buffer_pipe`.
+///
+/// All threads are joined before returning. When a codec fails, the codecs
+/// after it see the end of their input and the codecs before it fail with
+/// `BrokenPipe`.
+fn run_codecs_threaded<W: Write + ?Sized>(
+    input: &mut (dyn Read + Send),
+    codec_list: &[commands::Codec],
+    mode: codecs::CodecMode,
+    engine: Engine,
+    output: &mut W,
+    counters: Option<&[StageCounters]>,
+) -> Result<(), Error> {
+    std::thread::scope(|scope| {
+        let mut previous_input = Box::new(input) as Box<dyn Read + Send>;
+        let mut handles = Vec::with_capacity(codec_list.len());
+
+        for (index, c) in codec_list.iter().enumerate() {
+            let (reader, writer): (Box<dyn Read + Send>, Box<dyn Write + Send>) = match engine {
+                Engine::Threaded => {
+                    // TODO: (prof) PipeReader::BufRead::fill_buf, PipeWriter::Write::write use lots of tiny vec
+                    let (reader, writer) = pipe::pipe();
+                    (Box::new(reader), Box::new(writer))
                 }
                 _ => {
-                    anyhow::bail!("unknown option: {}", name);
+                    let (reader, writer) = buffer_pipe();
+                    (Box::new(reader), Box::new(writer))
                 }
-            },
-            commands::CommandOption::Value { name, text } => match name.as_ref() {
-                OPTION_INPUT_STRING => {
-                    let codec = commands::Codec {
-                        name: "const".to_string(),
-                        options: vec![commands::CommandOption::Value {
-                            name: "C".to_string(),
-                            text: text.clone(),
-                        }],
-                    };
+            };
+            let input = std::mem::replace(&mut previous_input, reader);
+            let counter = counters.map(|counters| &counters[index]);
 
-                    command.codecs.insert(0, codec);
-                }
-                OPTION_INPUT_FILE => {
-                    let codec = commands::Codec {
-                        name: "cat".to_string(),
-                        options: vec![
-                            commands::CommandOption::Switch("c".to_string()),
-                            commands::CommandOption::Value {
-                                name: "F".to_string(),
-                                text: text.clone(),
-                            },
-                        ],
+            let handle = std::thread::Builder::new()
+                .name(c.name.clone())
+                .spawn_scoped(scope, move || -> Result<()> {
+                    let (mut input, mut writer): (Box<dyn Read + Send>, Box<dyn Write + Send>) =
+                        match counter {
+                            Some(counter) => (
+                                Box::new(counter.reader(input)),
+                                Box::new(counter.writer(writer)),
+                            ),
+                            None => (input, writer),
+                        };
+                    let mut run = || -> Result<()> {
+                        run_codec(&mut input, c, mode, engine, &mut writer)?;
+                        writer.flush()?;
+                        Ok(())
                     };
+                    match counter {
+                        Some(counter) => counter.time(run),
+                        None => run(),
+                    }
+                })?;
+            handles.push(handle);
+        }
 
-                    command.codecs.insert(0, codec);
-                }
-                OPTION_OUTPUT_FILE => {
-                    let codec = commands::Codec {
-                        name: "redirect".to_string(),
-                        options: vec![commands::CommandOption::Value {
-                            name: "O".to_string(),
-                            text: text.clone(),
-                        }],
-                    };
-                    command.codecs.push(codec);
-                }
-                OPTION_HELP_CODEC => {
-                    let codec = commands::Codec {
-                        name: "usage".to_string(),
-                        options: vec![commands::CommandOption::Value {
-                            name: "C".to_string(),
-                            text: text.clone(),
-                        }],
-                    };
-                    command.codecs.push(codec);
-                }
+        let copy_result = std::io::copy(previous_input.deref_mut(), output);
+        // unblock the last codec if the output is closed early
+        drop(previous_input);
 
-                _ => {
-                    anyhow::bail!("unknown option: {}", name);
+        let results: Vec<_> = handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("codec panicked")))
+            })
+            .collect();
+
+        for (index, (result, c)) in results.into_iter().zip(codec_list).enumerate() {
+            if let Err(err) = result {
+                if is_broken_pipe(&err) {
+                    continue;
                 }
-            },
+                return Err(Error::Codec {
+                    name: c.name.clone(),
+                    index,
+                    source: err,
+                });
+            }
         }
-    }
 
-    run_codecs(
-        Box::new(std::io::stdin()),
-        command.codecs,
-        global_mode,
-        &mut std::io::stdout(),
-    )
+        match copy_result {
+            Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => Err(Error::Io(err)),
+            _ => Ok(()),
+        }
+    })
 }
 
-fn run_codecs<R: 'static + Read + ?Sized + Send, W: Write + ?Sized>(
-    input: Box<R>,
-    codec_list: Vec<commands::Codec>,
+/// All codecs on the current thread, the output of each codec (but the last
+/// one) collected in memory before running the next one.
+///
+/// This buffers every stage whole: a codec only returns once its input ends,
+/// so the next one cannot be run on chunks of its output without a thread.
+fn run_codecs_sequential<W: Write + ?Sized>(
+    input: &mut (dyn Read + Send),
+    codec_list: &[commands::Codec],
     mode: codecs::CodecMode,
     output: &mut W,
-) -> Result<()> {
-    let mut previous_input = Box::new(input) as Box<dyn Read + Send>;
-
-    for c in codec_list {
-        // TODO: (prof) PipeReader::BufRead::fill_buf, PipeWriter::Write::write use lots of tiny vec
-        let (reader, mut writer) = pipe::pipe();
-
-        std::thread::Builder::new()
-            .name(c.name.clone())
-            .spawn(move || {
-                (|| -> Result<()> {
-                    run_codec(&mut previous_input, &c, mode, &mut writer)?;
-                    writer.flush()?;
-                    Ok(())
-                })()
-                .unwrap_or_else(|err| {
-                    if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
-                        if io_err.kind() == std::io::ErrorKind::BrokenPipe {
-                            return;
-                        }
-                    }
-                    eprintln!("Error when executing codec {}: {}", c.name, err);
-                    std::process::exit(1)
-                });
-            })?;
+    counters: Option<&[StageCounters]>,
+) -> Result<(), Error> {
+    let Some((last, codec_list)) = codec_list.split_last() else {
+        return match std::io::copy(input, output) {
+            Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => Err(Error::Io(err)),
+            _ => Ok(()),
+        };
+    };
 
-        previous_input = Box::new(reader);
+    let mut previous_output: Option<Vec<u8>> = None;
+    let mut slice;
+    for (index, c) in codec_list.iter().enumerate() {
+        let input = match &previous_output {
+            Some(buffer) => {
+                slice = buffer.as_slice();
+                &mut slice as &mut (dyn Read + Send)
+            }
+            None => &mut *input,
+        };
+
+        let mut buffer = Vec::new();
+        run_counted(
+            counters.map(|counters| &counters[index]),
+            |input, output| run_codec(input, c, mode, Engine::Sequential, output),
+            input,
+            &mut buffer,
+        )
+        .map_err(|err| Error::Codec {
+            name: c.name.clone(),
+            index,
+            source: err,
+        })?;
+        previous_output = Some(buffer);
+    }
+
+    let input = match &previous_output {
+        Some(buffer) => {
+            slice = buffer.as_slice();
+            &mut slice as &mut (dyn Read + Send)
+        }
+        None => input,
+    };
+    let mut output = WholeWriter(output);
+    let counter = counters.map(|counters| &counters[codec_list.len()]);
+    let result = run_counted(
+        counter,
+        |input, output| {
+            run_codec(input, last, mode, Engine::Sequential, output)?;
+            Ok(output.flush()?)
+        },
+        input,
+        &mut output,
+    );
+    match result {
+        Err(err) if !is_broken_pipe(&err) => Err(Error::Codec {
+            name: last.name.clone(),
+            index: codec_list.len(),
+            source: err,
+        }),
+        _ => Ok(()),
     }
-    let _ = std::io::copy(previous_input.deref_mut(), output);
-    Ok(())
 }
 
-fn run_codec<R: Read + ?Sized, W: Write + ?Sized>(
+/// Runs a codec of the sequential engine, counting its input and output if
+/// `counter` is given.
+fn run_counted(
+    counter: Option<&StageCounters>,
+    run: impl FnOnce(&mut (dyn Read + Send), &mut dyn Write) -> Result<()>,
+    input: &mut (dyn Read + Send),
+    output: &mut dyn Write,
+) -> Result<()> {
+    match counter {
+        Some(counter) => {
+            let mut input = counter.reader(input);
+            let mut output = counter.writer(output);
+            counter.time(|| run(&mut input, &mut output))
+        }
+        None => run(input, output),
+    }
+}
+
+fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<std::io::Error>()
+        .is_some_and(|io_err| io_err.kind() == std::io::ErrorKind::BrokenPipe)
+}
+
+fn run_codec<R: Read + Send + ?Sized, W: Write + ?Sized>(
     mut input: &mut R,
     codec: &commands::Codec,
-    mut mode: codecs::CodecMode,
+    mode: codecs::CodecMode,
+    engine: Engine,
     mut output: &mut W,
 ) -> Result<()> {
     let codecs_info = CodecMetaInfo::instance();
-    let options = make_codec_options(codec)?;
+    let c = codecs_info
+        .lookup(&codec.name)
+        .ok_or_else(|| anyhow::anyhow!("codec not found: {}", codec.name))?;
+    let options = make_codec_options(codec, c.options().as_deref(), engine)?;
 
-    if options.get_switch("e") {
+    c.run_codec(
+        &mut input,
+        codec_mode(&options, mode),
+        &options,
+        &mut output,
+    )
+}
+
+/// The mode of a codec, given by its `e`/`d` switches or the global mode.
+fn codec_mode(options: &codecs::Options, mut mode: codecs::CodecMode) -> codecs::CodecMode {
+    if options.get_switch(SWITCH_ENCODING) {
         mode = codecs::CodecMode::Encoding;
     }
-    if options.get_switch("d") {
+    if options.get_switch(SWITCH_DECODING) {
         mode = codecs::CodecMode::Decoding;
     }
+    mode
+}
 
-    let c = codecs_info
-        .lookup(&codec.name)
-        .ok_or_else(|| anyhow::anyhow!("codec not found: {}", codec.name))?;
-    c.run_codec(&mut input, mode, &options, &mut output)
+/// Writes how `codec_list` would be run, without reading any input: each codec
+/// with its mode and options, the values of sub-codec options evaluated, and
+/// the codecs of chain options explained the same way (indented). Values of
+/// sensitive options are hidden if `redact` is set.
+pub(super) fn explain_codecs<W: Write + ?Sized>(
+    codec_list: &[commands::Codec],
+    mode: codecs::CodecMode,
+    redact: bool,
+    indent: usize,
+    output: &mut W,
+) -> Result<(), Error> {
+    let prefix = " ".repeat(indent);
+
+    for (index, codec) in codec_list.iter().enumerate() {
+        let c = CodecMetaInfo::instance()
+            .lookup(&codec.name)
+            .ok_or_else(|| Error::CodecNotFound(codec.name.clone()))?;
+        let specs = c.options().unwrap_or_default();
+        let options =
+            make_codec_options(codec, Some(&specs), Engine::Sequential).map_err(|source| {
+                Error::Codec {
+                    name: codec.name.clone(),
+                    index,
+                    source,
+                }
+            })?;
+        let mode = codec_mode(&options, mode);
+
+        write!(output, "{}{}. {} ({})", prefix, index + 1, codec.name, mode)?;
+        if let Some(expansion) = c.expansion(&options) {
+            write!(output, " = {}", expansion)?;
+        }
+        writeln!(output)?;
+
+        for option in &codec.options {
+            let name = option.name();
+            if MODE_SWITCHES.contains(&name) {
+                continue;
+            }
+            let sensitive = specs.iter().any(|spec| spec.name == name && spec.sensitive);
+
+            write!(output, "{}    -{}", prefix, name)?;
+            match option {
+                commands::CommandOption::Switch(_) => {}
+                commands::CommandOption::Value { .. } if redact && sensitive => {
+                    write!(output, " <redacted>")?
+                }
+                commands::CommandOption::Value { text, .. } => {
+                    let value = options.get_text_raw(name).unwrap_or_default();
+                    match text {
+                        commands::Text::Chain(codecs) => {
+                            writeln!(output)?;
+                            explain_codecs(codecs, mode, redact, indent + 8, output)?;
+                            continue;
+                        }
+                        commands::Text::Codecs { .. } => {
+                            write!(output, " {} = {}", text, format_value(value))?
+                        }
+                        _ => write!(output, " {}", format_value(value))?,
+                    }
+                }
+            }
+            writeln!(output)?;
+        }
+    }
+
+    Ok(())
 }
 
-fn make_codec_options(codec: &commands::Codec) -> Result<codecs::Options> {
+/// A quoted string if `value` is printable UTF-8, hex bytes otherwise.
+fn format_value(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(value) if !value.contains(char::is_control) => format!("{:?}", value),
+        _ => format!("0x{}", hex::encode(value)),
+    }
+}
+
+fn make_codec_options(
+    codec: &commands::Codec,
+    specs: Option<&[OptionSpec]>,
+    engine: Engine,
+) -> Result<codecs::Options> {
     let mut option = codecs::Options::new();
+    option.set_engine(engine);
 
     for o in &codec.options {
         match o {
@@ -175,18 +571,40 @@ fn make_codec_options(codec: &commands::Codec) -> Result<codecs::Options> {
                 commands::Text::String(value) => {
                     option.insert_text(name, value.as_bytes());
                 }
+                commands::Text::Bytes(value) => {
+                    option.insert_text(name, value);
+                }
                 commands::Text::Codecs { input, codecs } => {
                     let mut buf = Vec::<u8>::new();
 
                     run_codecs(
-                        Box::new(std::io::Cursor::new(input.clone())),
-                        codecs.clone(),
+                        &mut input.as_bytes(),
+                        codecs,
                         codecs::CodecMode::Encoding,
+                        Engine::Sequential,
                         &mut buf,
+                        None,
                     )?;
 
+                    let spec = specs
+                        .unwrap_or_default()
+                        .iter()
+                        .find(|spec| spec.name == name.as_str());
+                    if let Some(Err(reason)) = spec.map(|spec| spec.check_value(&buf)) {
+                        anyhow::bail!("option -{}: {}", name, reason);
+                    }
+
                     option.insert_text(name, &buf);
                 }
+                commands::Text::Chain(codecs) => {
+                    option.insert_chain(
+                        name,
+                        Chain {
+                            codecs: codecs.clone(),
+                            engine,
+                        },
+                    );
+                }
             },
         }
     }
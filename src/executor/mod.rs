@@ -1,5 +1,15 @@
 pub mod commands;
+mod engine;
+mod error;
+#[allow(clippy::module_inception)]
 mod executor;
 pub mod parser;
+mod pipeline;
+mod stats;
 
+pub use engine::Engine;
+pub use error::*;
 pub use executor::*;
+pub use parser::ParseError;
+pub use pipeline::*;
+pub use stats::StageStats;
@@ -1,82 +1,272 @@
-use crate::executor::commands;
+use std::{borrow::Cow, collections::VecDeque, fmt::Display, ops::Range};
 
-use anyhow::Result;
+use crate::{
+    codecs::{CodecMetaInfo, OptionKind, OptionSpec},
+    executor::{commands, mode_options, pipeline::OPTION_SCRIPT, Pipeline},
+};
+
+type Result<T> = std::result::Result<T, ParseError>;
 
 const OPENING_PARENTHESIS: &str = "[";
 const CLOSING_PARENTHESIS: &str = "]";
 const OPTION_PREFIX: &str = "-";
-// TODO: bug parser [ 'a [d]' hex]
-// TODO: check trailing
-pub struct Tokenizer {
-    look_next: Option<String>,
-    text: Vec<String>,
-    current_pos: usize,
-    eof: bool,
+const LONG_OPTION_PREFIX: &str = "--";
+const INLINE_VALUE_SEPARATOR: char = '=';
+/// Ends the options of a codec, or the global options.
+const END_OF_OPTIONS: &str = "--";
+const ESCAPE: char = '\\';
+
+/// An error in the command line, rendered with a caret under the offending
+/// token:
+///
+/// ```text
+/// unexpected ]
+///     base64 ] hex
+///            ^
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    message: String,
+    location: Option<Location>,
+    arguments: Vec<String>,
 }
 
-impl Tokenizer {
-    pub fn new(text: Vec<String>) -> Tokenizer {
-        Tokenizer {
-            text,
-            look_next: None,
-            current_pos: 0,
-            eof: false,
+/// Where a token comes from: the index of its argument and its byte range in
+/// the argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Location {
+    index: usize,
+    span: Range<usize>,
+}
+
+impl ParseError {
+    /// An error that is not located in the arguments.
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError {
+            message: message.into(),
+            location: None,
+            arguments: vec![],
         }
     }
 
-    fn next(&mut self) -> Option<String> {
-        if let Some(look_next) = self.look_next.take() {
-            return Some(look_next);
-        }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Index of the argument containing the offending token, or the number of
+    /// arguments if they ended unexpectedly. `None` if the error is not
+    /// located.
+    pub fn index(&self) -> Option<usize> {
+        self.location.as_ref().map(|location| location.index)
+    }
+
+    /// The offending token, `None` if the arguments ended unexpectedly.
+    pub fn token(&self) -> Option<&str> {
+        let location = self.location.as_ref()?;
+        self.arguments
+            .get(location.index)
+            .map(|argument| &argument[location.span.clone()])
+    }
+}
 
-        if self.eof {
-            return None;
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        let Some(location) = &self.location else {
+            return Ok(());
+        };
+
+        let mut line = String::new();
+        let mut caret = String::new();
+        for (index, argument) in self.arguments.iter().enumerate() {
+            if index > 0 {
+                line.push(' ');
+            }
+            let column = line.chars().count();
+            let quoted = quote_argument(argument);
+            line.push_str(&quoted);
+
+            if index == location.index {
+                // the token is underlined as a whole in a quoted argument
+                let (start, width) = if quoted.len() == argument.len() {
+                    (
+                        column + argument[..location.span.start].chars().count(),
+                        argument[location.span.clone()].chars().count(),
+                    )
+                } else {
+                    (column, quoted.chars().count())
+                };
+                caret = format!("{}{}", " ".repeat(start), "^".repeat(width.max(1)));
+            }
+        }
+        // end of the arguments
+        if location.index >= self.arguments.len() {
+            let column = line.chars().count();
+            caret = format!("{}^", " ".repeat(column + usize::from(column > 0)));
         }
 
-        let next = std::mem::take(&mut self.text[self.current_pos]);
-        self.current_pos += 1;
+        write!(f, "\n    {}\n    {}", line, caret)
+    }
+}
 
-        if next.starts_with(OPENING_PARENTHESIS) && next != OPENING_PARENTHESIS {
-            self.look_next = Some(next[OPENING_PARENTHESIS.len()..].to_string());
-            return Some(OPENING_PARENTHESIS.to_string());
+impl std::error::Error for ParseError {}
+
+/// Quotes an argument that would not be a single argument in shell, the way it
+/// would likely be written.
+fn quote_argument(argument: &str) -> Cow<'_, str> {
+    if argument.is_empty() || argument.contains(char::is_whitespace) {
+        format!("'{}'", argument.replace('\'', "'\\''")).into()
+    } else {
+        argument.into()
+    }
+}
+
+/// Splits command line arguments into brackets and words.
+///
+/// Each argument is split as follows:
+/// 1. if it starts with `\[` or `\]` (after any number of `\`), it is a word
+///    without its first `\`, e.g. `\[1,2]` is the word `[1,2]`, `\]` is `]`
+///    and `\\[` is `\[`
+/// 2. if it is empty, it is an empty word
+/// 3. otherwise, each leading `[` and each trailing `]` is a bracket, and the
+///    rest (if not empty) is a word, e.g. `[base64` is `[` `base64`, and
+///    `hex]]` is `hex` `]` `]`. A `\` before the trailing `]`s makes the first
+///    of them part of the word, e.g. `a\]` is the word `a]`
+///
+/// Whitespaces don't matter, e.g. `'[1, 2]'` is `[` `1, 2` `]`, while
+/// `'\[1, 2]'` is the word `[1, 2]`.
+pub struct Tokenizer<'m> {
+    tokens: VecDeque<(Token, Location)>,
+    arguments: Vec<String>,
+    /// location of the last token taken, `None` after the end
+    current: Option<Location>,
+    /// registry of the codecs giving their option specs, the global one if
+    /// `None`
+    meta_info: Option<&'m CodecMetaInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Open => write!(f, "{}", OPENING_PARENTHESIS),
+            Token::Close => write!(f, "{}", CLOSING_PARENTHESIS),
+            Token::Word(word) => write!(f, "{}", word),
         }
+    }
+}
 
-        if next.ends_with(CLOSING_PARENTHESIS) && next != CLOSING_PARENTHESIS {
-            self.look_next = Some(CLOSING_PARENTHESIS.to_string());
-            return Some(next[..next.len() - CLOSING_PARENTHESIS.len()].to_string());
+impl<'m> Tokenizer<'m> {
+    pub fn new(text: Vec<String>) -> Self {
+        let mut tokens = VecDeque::with_capacity(text.len());
+        for (index, arg) in text.iter().enumerate() {
+            for (token, span) in split_argument(arg) {
+                tokens.push_back((token, Location { index, span }));
+            }
         }
 
-        Some(next)
+        Tokenizer {
+            tokens,
+            arguments: text,
+            current: None,
+            meta_info: None,
+        }
     }
 
-    fn peek(&mut self) -> Option<&str> {
-        if self.look_next.is_some() {
-            return self.look_next.as_deref();
+    /// Looks up the options of codecs in `meta_info` instead of the global
+    /// registry, e.g. while it is being built.
+    pub fn with_meta_info(text: Vec<String>, meta_info: &'m CodecMetaInfo) -> Self {
+        Tokenizer {
+            meta_info: Some(meta_info),
+            ..Tokenizer::new(text)
         }
-        if self.current_pos >= self.text.len() {
-            self.eof = true;
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let (token, location) = self.tokens.pop_front().unzip();
+        self.current = location;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.front().map(|(token, _)| token)
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        match self.peek()? {
+            Token::Word(word) => Some(word),
+            _ => None,
         }
+    }
 
-        if self.eof {
-            return None;
+    /// An error at the last token taken, or at the end of the arguments.
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        let location = self.current.clone().unwrap_or(Location {
+            index: self.arguments.len(),
+            span: 0..0,
+        });
+        ParseError {
+            message: message.into(),
+            location: Some(location),
+            arguments: self.arguments.clone(),
         }
+    }
+}
 
-        let next = &self.text[self.current_pos];
+fn split_argument(arg: &str) -> Vec<(Token, Range<usize>)> {
+    let unescaped = arg.trim_start_matches(ESCAPE);
+    if unescaped.len() < arg.len()
+        && (unescaped.starts_with(OPENING_PARENTHESIS)
+            || unescaped.starts_with(CLOSING_PARENTHESIS))
+    {
+        let word = arg[ESCAPE.len_utf8()..].to_string();
+        return vec![(Token::Word(word), 0..arg.len())];
+    }
 
-        if next.starts_with(OPENING_PARENTHESIS) && next != OPENING_PARENTHESIS {
-            return Some(OPENING_PARENTHESIS);
-        }
+    if arg.is_empty() {
+        return vec![(Token::Word(String::new()), 0..0)];
+    }
 
-        if next.ends_with(CLOSING_PARENTHESIS) && next != CLOSING_PARENTHESIS {
-            return Some(&next[..next.len() - CLOSING_PARENTHESIS.len()]);
+    let mut tokens = vec![];
+    let mut start = 0;
+    while arg[start..].starts_with(OPENING_PARENTHESIS) {
+        tokens.push((Token::Open, start..start + OPENING_PARENTHESIS.len()));
+        start += OPENING_PARENTHESIS.len();
+    }
+
+    let mut end = arg.len();
+    while end > start && arg[..end].ends_with(CLOSING_PARENTHESIS) {
+        end -= CLOSING_PARENTHESIS.len();
+    }
+
+    let mut word_end = end;
+    let word = match arg[start..end].strip_suffix(ESCAPE) {
+        Some(rest) if end < arg.len() => {
+            word_end += CLOSING_PARENTHESIS.len();
+            format!("{}{}", rest, CLOSING_PARENTHESIS)
         }
+        _ => arg[start..end].to_string(),
+    };
+    if !word.is_empty() {
+        tokens.push((Token::Word(word), start..word_end));
+    }
 
-        Some(next)
+    while word_end < arg.len() {
+        let close_end = word_end + CLOSING_PARENTHESIS.len();
+        tokens.push((Token::Close, word_end..close_end));
+        word_end = close_end;
     }
+    tokens
 }
 
 pub fn parse_command(tokenizer: &mut Tokenizer) -> Result<commands::Command> {
-    let options = parse_options(tokenizer)?;
+    let options = parse_options(tokenizer, &Pipeline::options())?;
 
     let mut codecs = vec![];
 
@@ -84,91 +274,363 @@ pub fn parse_command(tokenizer: &mut Tokenizer) -> Result<commands::Command> {
         codecs.push(codec);
     }
 
+    if let Some(token) = tokenizer.next() {
+        return Err(tokenizer.error(format!("unexpected {}", token)));
+    }
+
     Ok(commands::Command { options, codecs })
 }
 
-fn is_special_token(token: &str) -> bool {
-    token == OPENING_PARENTHESIS || token == CLOSING_PARENTHESIS
-}
+/// Parses a whole chain of codecs without global options, e.g. the definition
+/// of an alias.
+pub(crate) fn parse_codecs(tokenizer: &mut Tokenizer) -> Result<Vec<commands::Codec>> {
+    let mut codecs = vec![];
 
-fn parse_codec(tokenizer: &mut Tokenizer) -> Result<Option<commands::Codec>> {
-    let name = tokenizer.peek().unwrap_or_default();
-    if name.is_empty() || is_special_token(name) {
-        return Ok(None);
+    while let Some(codec) = parse_codec(tokenizer)? {
+        codecs.push(codec);
     }
-    let name = name.to_owned();
 
-    tokenizer.next();
+    if let Some(token) = tokenizer.next() {
+        return Err(tokenizer.error(format!("unexpected {}", token)));
+    }
 
-    let options = parse_options(tokenizer)?;
+    Ok(codecs)
+}
 
-    Ok(Some(commands::Codec { name, options }))
+/// Parses a chain written as a single string, e.g. in a script or the
+/// aliases file. See [`split_str`] for the syntax.
+pub fn parse_str(text: &str) -> Result<commands::Command> {
+    parse_command(&mut Tokenizer::new(split_str(text)?))
 }
 
-fn parse_options(tokenizer: &mut Tokenizer) -> Result<Vec<commands::CommandOption>> {
-    let mut options = vec![];
+/// Splits `text` into arguments the way a shell would, before they are
+/// tokenized as command line arguments:
+/// - arguments are separated by whitespaces, including newlines
+/// - `#` at the start of an argument comments out the rest of the line
+/// - `'...'` is taken literally, and `"..."` too except that `\"` and `\\`
+///   are a `"` and a `\`
+/// - elsewhere, `\` escapes the next character, and is dropped with a
+///   following newline
+///
+/// Quotes do not end an argument, e.g. `-K 'a b'c` has the argument `a bc`.
+pub fn split_str(text: &str) -> Result<Vec<String>> {
+    // the line is only worth telling in a text of several lines
+    let error = |line: usize, message: String| {
+        if text.trim_end().contains('\n') {
+            ParseError::new(format!("line {}: {}", line, message))
+        } else {
+            ParseError::new(message)
+        }
+    };
+    let mut arguments = vec![];
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+
     loop {
-        let option_name = tokenizer.peek().unwrap_or_default();
-        if !option_name.starts_with(OPTION_PREFIX) {
+        while let Some(c) = chars.next_if(|c| c.is_whitespace()) {
+            line += usize::from(c == '\n');
+        }
+        if chars.next_if_eq(&'#').is_some() {
+            while chars.next_if(|c| *c != '\n').is_some() {}
+            continue;
+        }
+        if chars.peek().is_none() {
             break;
         }
-        let option_name = option_name[OPTION_PREFIX.len()..].to_string();
 
-        tokenizer.next();
+        let mut argument = String::new();
+        // `''` is an empty argument, but an escaped newline alone is nothing
+        let mut quoted = false;
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            match c {
+                '\'' | '"' => {
+                    quoted = true;
+                    let quote_line = line;
+                    loop {
+                        match chars.next() {
+                            Some(next) if next == c => break,
+                            Some(ESCAPE)
+                                if c == '"' && matches!(chars.peek(), Some(&('"' | ESCAPE))) =>
+                            {
+                                argument.extend(chars.next())
+                            }
+                            Some(next) => {
+                                line += usize::from(next == '\n');
+                                argument.push(next);
+                            }
+                            None => return Err(error(quote_line, format!("unterminated {}", c))),
+                        }
+                    }
+                }
+                ESCAPE => match chars.next() {
+                    Some('\n') => line += 1,
+                    Some(next) => argument.push(next),
+                    None => return Err(error(line, format!("unexpected end after {}", ESCAPE))),
+                },
+                _ => argument.push(c),
+            }
+        }
+        if quoted || !argument.is_empty() {
+            arguments.push(argument);
+        }
+    }
 
-        let option: commands::CommandOption;
+    Ok(arguments)
+}
 
-        let first = option_name.chars().next().expect("empty option");
-        if first.is_uppercase() {
-            option = commands::CommandOption::Value {
-                name: option_name,
-                text: parse_text(tokenizer)?,
-            }
-        } else {
-            option = commands::CommandOption::Switch(option_name);
+/// Replaces each `-f script` (or `--script script`) among the global options
+/// of `args` with the arguments read from the file `script`, see
+/// [`split_str`]. The options are walked as [`parse_command`] does, so the
+/// value of another option is never taken for `-f`.
+pub(crate) fn expand_scripts(args: Vec<String>) -> Result<Vec<String>> {
+    let specs = Pipeline::options();
+    let mut result = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == END_OF_OPTIONS || !arg.starts_with(OPTION_PREFIX) {
+            result.push(arg);
+            break;
         }
 
-        options.push(option);
+        let (name, inline_value) = match arg.split_once(INLINE_VALUE_SEPARATOR) {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        // unknown long names are left for the parser to report
+        let name = match name.strip_prefix(LONG_OPTION_PREFIX) {
+            Some(long) => specs
+                .iter()
+                .find(|spec| spec.long == Some(long))
+                .map_or(long, |spec| spec.name.as_ref()),
+            None => &name[OPTION_PREFIX.len()..],
+        };
+
+        if name == OPTION_SCRIPT {
+            let path = match inline_value {
+                Some(path) => path.to_string(),
+                None => args.next().ok_or_else(|| {
+                    ParseError::new(format!("-{}: expect a script file", OPTION_SCRIPT))
+                })?,
+            };
+            let script = std::fs::read_to_string(&path)
+                .map_err(|err| ParseError::new(format!("{}: {}", path, err)))?;
+            let script =
+                split_str(&script).map_err(|err| ParseError::new(format!("{}: {}", path, err)))?;
+            result.extend(script);
+            continue;
+        }
+
+        let takes_value = inline_value.is_none() && takes_next_value(name, &specs);
+        result.push(arg);
+        if takes_value {
+            result.extend(args.next());
+        }
     }
 
-    Ok(options)
+    result.extend(args);
+    Ok(result)
 }
 
-fn parse_text(tokenizer: &mut Tokenizer) -> Result<commands::Text> {
-    let str = tokenizer.peek();
+/// Writes `value` as a word of [`split_str`] that is read back as is, or
+/// `None` if it cannot be written this way (e.g. `a]]`).
+pub(crate) fn format_word(value: &str) -> Option<String> {
+    if value.is_empty() {
+        return Some(format!("{}{}", OPENING_PARENTHESIS, CLOSING_PARENTHESIS));
+    }
+
+    let mut candidates = vec![value.to_string(), format!("{}{}", ESCAPE, value)];
+    if let Some(rest) = value.strip_suffix(CLOSING_PARENTHESIS) {
+        candidates.push(format!("{}{}{}", rest, ESCAPE, CLOSING_PARENTHESIS));
+    }
+
+    let argument = candidates.into_iter().find(|argument| {
+        matches!(&split_argument(argument)[..], [(Token::Word(word), _)] if word == value)
+    })?;
 
-    // TODO: allow empty option value
-    if str.is_none() {
-        anyhow::bail!("EOF when parsing");
+    let special = |c: char| c.is_whitespace() || matches!(c, '\'' | '"' | '#' | ESCAPE);
+    if argument.contains(special) {
+        Some(format!("'{}'", argument.replace('\'', "'\\''")))
+    } else {
+        Some(argument)
     }
+}
 
-    let str = str.unwrap().to_string();
+fn parse_codec(tokenizer: &mut Tokenizer) -> Result<Option<commands::Codec>> {
+    let name = match tokenizer.peek_word() {
+        Some(name) if !name.is_empty() => name.to_owned(),
+        _ => return Ok(None),
+    };
 
     tokenizer.next();
 
-    // TODO: escape parenthesis
-    let text = if str == OPENING_PARENTHESIS {
-        let input = tokenizer.next().unwrap_or_default();
-        // empty sub-codec syntax: [ [] cat -c ]
-        if input == CLOSING_PARENTHESIS {
-            return Ok(commands::Text::String(Default::default()));
-        }
+    let meta_info = match tokenizer.meta_info {
+        Some(meta_info) => meta_info,
+        None => CodecMetaInfo::instance(),
+    };
+    let options = parse_options(tokenizer, &codec_option_specs_in(meta_info, &name))?;
+
+    Ok(Some(commands::Codec { name, options }))
+}
+
+/// The option specs of the codec `name`, followed by the mode options.
+pub(crate) fn codec_option_specs(name: &str) -> Vec<OptionSpec> {
+    codec_option_specs_in(CodecMetaInfo::instance(), name)
+}
+
+fn codec_option_specs_in(meta_info: &CodecMetaInfo, name: &str) -> Vec<OptionSpec> {
+    let mut specs = meta_info
+        .lookup(name)
+        .and_then(|codec| codec.options())
+        .unwrap_or_default();
+    specs.extend(mode_options());
+    specs
+}
+
+/// Whether the option `name` takes the next word as its value, i.e. is
+/// declared with a value in `specs`, or is undeclared and capitalized. Other
+/// options take a value only inline (`-x=value`).
+pub(crate) fn takes_next_value(name: &str, specs: &[OptionSpec]) -> bool {
+    match specs
+        .iter()
+        .find(|spec| spec.name == name)
+        .map(|spec| &spec.kind)
+    {
+        Some(OptionKind::Switch) => false,
+        Some(_) => true,
+        None => name.starts_with(char::is_uppercase),
+    }
+}
+
+/// Parses options until a word that is not an option, or `--`.
+///
+/// Whether an option takes a value, and which kind of value, is given by its
+/// spec in `specs`. Options without a spec take a value if their name starts
+/// with an uppercase letter, or if it is given inline (`-x=value`). Long
+/// names (`--key`) must be declared, and are replaced by the short ones.
+fn parse_options(
+    tokenizer: &mut Tokenizer,
+    specs: &[OptionSpec],
+) -> Result<Vec<commands::CommandOption>> {
+    let mut options = vec![];
+    while let Some(word) = tokenizer
+        .peek_word()
+        .filter(|word| word.starts_with(OPTION_PREFIX))
+    {
+        let word = word.to_string();
 
-        let mut codecs = vec![];
+        tokenizer.next();
 
-        while let Some(codec) = parse_codec(tokenizer)? {
-            codecs.push(codec);
+        if word == END_OF_OPTIONS {
+            break;
         }
 
-        let n = tokenizer.next().unwrap_or_default();
-        if n != CLOSING_PARENTHESIS {
-            anyhow::bail!("expect {}, found {}", CLOSING_PARENTHESIS, n);
+        let (name, inline_value) = match word.split_once(INLINE_VALUE_SEPARATOR) {
+            Some((name, value)) => (name, Some(value)),
+            None => (word.as_str(), None),
+        };
+        let (name, spec) = match name.strip_prefix(LONG_OPTION_PREFIX) {
+            Some(long) => {
+                let spec = specs
+                    .iter()
+                    .find(|spec| spec.long == Some(long))
+                    .ok_or_else(|| tokenizer.error(format!("unknown option {}", name)))?;
+                (spec.name.to_string(), Some(spec))
+            }
+            None => {
+                let name = &name[OPTION_PREFIX.len()..];
+                if name.is_empty() {
+                    return Err(tokenizer.error("empty option name"));
+                }
+                (
+                    name.to_string(),
+                    specs.iter().find(|spec| spec.name == name),
+                )
+            }
+        };
+
+        let takes_value =
+            takes_next_value(&name, specs) || (spec.is_none() && inline_value.is_some());
+        let option = match inline_value {
+            Some(_) if !takes_value => {
+                return Err(tokenizer.error(format!("option -{} takes no value", name)))
+            }
+            Some(_) if matches!(spec.map(|spec| &spec.kind), Some(OptionKind::Chain)) => {
+                return Err(tokenizer.error(format!("option -{} expects a chain [codecs]", name)))
+            }
+            Some(value) => commands::CommandOption::Value {
+                name,
+                text: commands::Text::String(value.to_string()),
+            },
+            None if !takes_value => commands::CommandOption::Switch(name),
+            None => {
+                let text = match spec.map(|spec| &spec.kind) {
+                    Some(OptionKind::Chain) => parse_chain(tokenizer)?,
+                    _ => parse_text(tokenizer)?,
+                };
+                commands::CommandOption::Value { name, text }
+            }
+        };
+
+        options.push(option);
+    }
+
+    Ok(options)
+}
+
+fn parse_text(tokenizer: &mut Tokenizer) -> Result<commands::Text> {
+    let text = match tokenizer.next() {
+        None => return Err(tokenizer.error("expect a value, found EOF")),
+        Some(Token::Word(str)) => commands::Text::String(str),
+        Some(Token::Close) => {
+            return Err(tokenizer.error(format!("unexpected {}", CLOSING_PARENTHESIS)))
         }
+        Some(Token::Open) => {
+            let input = match tokenizer.next() {
+                // empty string: []
+                Some(Token::Close) => return Ok(commands::Text::String(Default::default())),
+                // empty sub-codec input: [ [] cat -c ]
+                Some(Token::Open) => {
+                    expect(tokenizer, Token::Close)?;
+                    String::new()
+                }
+                Some(Token::Word(input)) => input,
+                None => return Err(tokenizer.error("expect a value, found EOF")),
+            };
+
+            let mut codecs = vec![];
+
+            while let Some(codec) = parse_codec(tokenizer)? {
+                codecs.push(codec);
+            }
 
-        commands::Text::Codecs { input, codecs }
-    } else {
-        commands::Text::String(str)
+            expect(tokenizer, Token::Close)?;
+
+            commands::Text::Codecs { input, codecs }
+        }
     };
 
     Ok(text)
 }
+
+fn parse_chain(tokenizer: &mut Tokenizer) -> Result<commands::Text> {
+    expect(tokenizer, Token::Open)?;
+
+    let mut codecs = vec![];
+
+    while let Some(codec) = parse_codec(tokenizer)? {
+        codecs.push(codec);
+    }
+
+    expect(tokenizer, Token::Close)?;
+
+    Ok(commands::Text::Chain(codecs))
+}
+
+fn expect(tokenizer: &mut Tokenizer, expected: Token) -> Result<()> {
+    match tokenizer.next() {
+        Some(token) if token == expected => Ok(()),
+        Some(token) => Err(tokenizer.error(format!("expect {}, found {}", expected, token))),
+        None => Err(tokenizer.error(format!("expect {}, found EOF", expected))),
+    }
+}
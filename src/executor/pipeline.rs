@@ -0,0 +1,414 @@
+use std::io::{Read, Write};
+
+use crate::{
+    codecs::{CodecMetaInfo, CodecMode, OptionSpec},
+    executor::{
+        check_codecs, commands, explain_codecs, has_switch,
+        parser::{self, ParseError},
+        reverse_codecs, run_codecs,
+        stats::{report_stats, StatsFormat},
+        Engine, Error, StageStats,
+    },
+};
+
+const OPTION_ENCODING: &str = "e";
+const OPTION_DECODING: &str = "d";
+const OPTION_NEW_LINE: &str = "n";
+const OPTION_REVERSE: &str = "r";
+const OPTION_INPUT_STRING: &str = "I";
+const OPTION_INPUT_FILE: &str = "F";
+const OPTION_OUTPUT_FILE: &str = "O";
+const OPTION_ENGINE: &str = "E";
+const OPTION_EXPLAIN: &str = "x";
+const OPTION_REDACT: &str = "k";
+const OPTION_STATS: &str = "s";
+const OPTION_STATS_JSON: &str = "j";
+const OPTION_HELP: &str = "h";
+const OPTION_HELP_CODEC: &str = "H";
+pub(crate) const OPTION_SCRIPT: &str = "f";
+
+/// A chain of codecs together with the global coding mode and the [`Engine`]
+/// running it.
+///
+/// Build it from command line arguments with [`Pipeline::parse`], or by hand:
+///
+/// ```no_run
+/// # use codec_rust::Pipeline;
+/// let pipeline = Pipeline::new()
+///     .codec("aes-ecb")
+///     .text("K", b"1234567890123456")
+///     .codec("base64")
+///     .switch("u");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Pipeline {
+    mode: CodecMode,
+    engine: Engine,
+    codecs: Vec<commands::Codec>,
+    /// `run` explains the pipeline instead (`-x`), hiding sensitive values if
+    /// `redact` (`-k`).
+    explain: bool,
+    redact: bool,
+    /// `run` reports the statistics of each codec to stderr (`-s`, `-j`).
+    stats: Option<StatsFormat>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The global options accepted by [`Pipeline::parse`].
+    pub fn options() -> Vec<OptionSpec> {
+        vec![
+            OptionSpec::switch(
+                OPTION_ENCODING,
+                "set the global coding mode to encode (default)",
+            )
+            .long("encode"),
+            OptionSpec::switch(OPTION_DECODING, "set the global coding mode to decode")
+                .long("decode"),
+            OptionSpec::switch(OPTION_REVERSE, "reverse the chain").long("reverse"),
+            OptionSpec::switch(OPTION_NEW_LINE, "append new line at the end of output")
+                .long("newline"),
+            OptionSpec::text(OPTION_INPUT_STRING, "string", "use `string` as input").long("input"),
+            OptionSpec::text(OPTION_INPUT_FILE, "file", "use content of `file` as input")
+                .long("input-file"),
+            OptionSpec::text(OPTION_OUTPUT_FILE, "file", "use `file` as output")
+                .long("output-file"),
+            OptionSpec::text(OPTION_ENGINE, "engine", "how codecs are connected")
+                .long("engine")
+                .one_of(Engine::NAMES)
+                .with_default("threaded"),
+            OptionSpec::switch(OPTION_EXPLAIN, "print the resolved pipeline and exit")
+                .long("explain"),
+            OptionSpec::switch(
+                OPTION_REDACT,
+                "with -x, hide the values of sensitive options",
+            )
+            .long("redact"),
+            OptionSpec::switch(
+                OPTION_STATS,
+                "report bytes and time of each codec to stderr",
+            )
+            .long("stats"),
+            OptionSpec::switch(OPTION_STATS_JSON, "like -s, as a line of JSON").long("stats-json"),
+            OptionSpec::switch(OPTION_HELP, "print usage and exit").long("help"),
+            OptionSpec::text(
+                OPTION_HELP_CODEC,
+                "codec",
+                "print usage for `codec` and exit",
+            )
+            .long("help-codec"),
+            OptionSpec::text(
+                OPTION_SCRIPT,
+                "script",
+                "read options and codecs from `script` file",
+            )
+            .long("script"),
+        ]
+    }
+
+    /// Parses command line arguments (without the program name), including
+    /// global options such as `-d` or `-I`, and `-f script` replaced by the
+    /// content of the script.
+    pub fn parse<I, S>(args: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let args = parser::expand_scripts(args.into_iter().map(Into::into).collect())
+            .map_err(Error::Parse)?;
+        let mut tokenizer = parser::Tokenizer::new(args);
+        let command = parser::parse_command(&mut tokenizer).map_err(Error::Parse)?;
+        Self::from_command(command)
+    }
+
+    /// Parses a chain written as a single string, with shell-like quoting and
+    /// `#` comments (see [`parser::split_str`]), e.g.
+    /// `"-d base64 zlib # decompress"`.
+    pub fn parse_str(text: &str) -> Result<Self, Error> {
+        let command = parser::parse_str(text).map_err(Error::Parse)?;
+        Self::from_command(command)
+    }
+
+    /// Resolves the global options of `command` into a pipeline.
+    pub fn from_command(command: commands::Command) -> Result<Self, Error> {
+        let mut pipeline = Pipeline {
+            mode: CodecMode::Encoding,
+            engine: Engine::default(),
+            codecs: command.codecs,
+            explain: false,
+            redact: false,
+            stats: None,
+        };
+
+        // the chain is reversed before any codec is added by the other options,
+        // in the global mode given by the last `-e`/`-d`
+        let reverse = has_switch(&command.options, OPTION_REVERSE);
+        if reverse {
+            for o in &command.options {
+                match o {
+                    commands::CommandOption::Switch(name) if name == OPTION_ENCODING => {
+                        pipeline.mode = CodecMode::Encoding
+                    }
+                    commands::CommandOption::Switch(name) if name == OPTION_DECODING => {
+                        pipeline.mode = CodecMode::Decoding
+                    }
+                    _ => {}
+                }
+            }
+            pipeline = pipeline.reverse()?;
+        }
+
+        for o in &command.options {
+            match o {
+                commands::CommandOption::Switch(name) => match name.as_ref() {
+                    OPTION_ENCODING => pipeline.mode = CodecMode::Encoding,
+                    OPTION_DECODING => pipeline.mode = CodecMode::Decoding,
+                    OPTION_REVERSE => {}
+                    OPTION_EXPLAIN => pipeline.explain = true,
+                    OPTION_REDACT => pipeline.redact = true,
+                    OPTION_STATS => pipeline.stats = Some(StatsFormat::Text),
+                    OPTION_STATS_JSON => pipeline.stats = Some(StatsFormat::Json),
+                    OPTION_NEW_LINE => pipeline.codecs.push(commands::Codec {
+                        name: "newline".to_string(),
+                        options: vec![],
+                    }),
+                    OPTION_HELP => {
+                        let codec = commands::Codec {
+                            name: "usage".to_string(),
+                            options: vec![],
+                        };
+                        pipeline.codecs = vec![codec];
+                    }
+                    _ => {
+                        return Err(Error::UnknownOption(name.clone()));
+                    }
+                },
+                commands::CommandOption::Value { name, text } => match name.as_ref() {
+                    OPTION_INPUT_STRING => {
+                        let codec = commands::Codec {
+                            name: "const".to_string(),
+                            options: vec![commands::CommandOption::Value {
+                                name: "C".to_string(),
+                                text: text.clone(),
+                            }],
+                        };
+
+                        pipeline.codecs.insert(0, codec);
+                    }
+                    OPTION_INPUT_FILE => {
+                        let codec = commands::Codec {
+                            name: "cat".to_string(),
+                            options: vec![
+                                commands::CommandOption::Switch("c".to_string()),
+                                commands::CommandOption::Value {
+                                    name: "F".to_string(),
+                                    text: text.clone(),
+                                },
+                            ],
+                        };
+
+                        pipeline.codecs.insert(0, codec);
+                    }
+                    OPTION_OUTPUT_FILE => {
+                        let codec = commands::Codec {
+                            name: "redirect".to_string(),
+                            options: vec![commands::CommandOption::Value {
+                                name: "O".to_string(),
+                                text: text.clone(),
+                            }],
+                        };
+                        pipeline.codecs.push(codec);
+                    }
+                    OPTION_ENGINE => {
+                        pipeline.engine = match text {
+                            commands::Text::String(engine) => {
+                                engine.parse().map_err(|err: anyhow::Error| {
+                                    Error::Parse(ParseError::new(err.to_string()))
+                                })?
+                            }
+                            _ => {
+                                return Err(Error::Parse(ParseError::new(format!(
+                                    "-{}: expect an engine name",
+                                    OPTION_ENGINE
+                                ))))
+                            }
+                        };
+                    }
+                    OPTION_SCRIPT => {
+                        return Err(Error::Parse(ParseError::new(format!(
+                            "-{}: scripts are only read from the command line",
+                            OPTION_SCRIPT
+                        ))))
+                    }
+                    OPTION_HELP_CODEC => {
+                        let codec = commands::Codec {
+                            name: "usage".to_string(),
+                            options: vec![commands::CommandOption::Value {
+                                name: "C".to_string(),
+                                text: text.clone(),
+                            }],
+                        };
+                        pipeline.codecs.push(codec);
+                    }
+
+                    _ => {
+                        return Err(Error::UnknownOption(name.clone()));
+                    }
+                },
+            }
+        }
+
+        Ok(pipeline)
+    }
+
+    /// Sets the global coding mode, which can be overwritten per codec with
+    /// the `e`/`d` switches.
+    pub fn mode(mut self, mode: CodecMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the engine running the chain, [`Engine::Threaded`] by default.
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Appends a codec to the end of the chain.
+    pub fn codec(mut self, name: &str) -> Self {
+        self.codecs.push(commands::Codec {
+            name: name.to_string(),
+            options: vec![],
+        });
+        self
+    }
+
+    /// Adds a switch option to the last codec.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no codec has been added yet.
+    pub fn switch(mut self, name: &str) -> Self {
+        self.last_codec()
+            .options
+            .push(commands::CommandOption::Switch(name.to_string()));
+        self
+    }
+
+    /// Adds a text option to the last codec.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no codec has been added yet.
+    pub fn text(mut self, name: &str, value: impl AsRef<[u8]>) -> Self {
+        self.last_codec()
+            .options
+            .push(commands::CommandOption::Value {
+                name: name.to_string(),
+                text: commands::Text::Bytes(value.as_ref().to_vec()),
+            });
+        self
+    }
+
+    /// Returns the inverse of the chain: the codecs in reverse order, each one
+    /// in the opposite of the mode it would run in. For example, the inverse of
+    /// `zlib base64` is `base64 -d zlib -d`.
+    ///
+    /// Fails with [`Error::NotInvertible`] if a codec cannot be inverted, see
+    /// [`Codec::is_invertible`](crate::Codec::is_invertible).
+    pub fn reverse(mut self) -> Result<Self, Error> {
+        self.codecs = reverse_codecs(&self.codecs, self.mode)?;
+        Ok(self)
+    }
+
+    /// Runs the chain, reading from `input` and writing the result to `output`,
+    /// or writes [the explanation](Pipeline::explain) of the chain if `-x` was
+    /// given. With `-s` or `-j`, the statistics of each codec are reported to
+    /// stderr when the chain ends, even if it failed.
+    ///
+    /// On error, `output` may already contain the part of the result that was
+    /// produced before the failing codec stopped.
+    pub fn run<R, W>(&self, mut input: R, output: &mut W) -> Result<(), Error>
+    where
+        R: Read + Send,
+        W: Write + ?Sized,
+    {
+        if self.explain {
+            return self.explain(self.redact, output);
+        }
+        let Some(format) = self.stats else {
+            check_codecs(CodecMetaInfo::instance(), &self.codecs)?;
+            return run_codecs(
+                &mut input,
+                &self.codecs,
+                self.mode,
+                self.engine,
+                output,
+                None,
+            );
+        };
+
+        let mut stats = vec![];
+        let start = std::time::Instant::now();
+        let result = self.run_with_stats(input, output, &mut stats);
+        if !stats.is_empty() {
+            report_stats(
+                &mut std::io::stderr(),
+                format,
+                &stats,
+                self.engine,
+                start.elapsed(),
+            )?;
+        }
+        result
+    }
+
+    /// Runs the chain like [`Pipeline::run`] (ignoring `-x`, `-s` and `-j`),
+    /// appending the statistics of each codec, in chain order, to `stats`.
+    ///
+    /// The statistics are appended once the chain ends, even if it failed,
+    /// unless it did not start (e.g. an unknown codec).
+    pub fn run_with_stats<R, W>(
+        &self,
+        mut input: R,
+        output: &mut W,
+        stats: &mut Vec<StageStats>,
+    ) -> Result<(), Error>
+    where
+        R: Read + Send,
+        W: Write + ?Sized,
+    {
+        check_codecs(CodecMetaInfo::instance(), &self.codecs)?;
+
+        run_codecs(
+            &mut input,
+            &self.codecs,
+            self.mode,
+            self.engine,
+            output,
+            Some(stats),
+        )
+    }
+
+    /// Writes the chain as it would be run, without reading any input: the
+    /// codecs (after the expansion of global options such as `-I` or `-n`) with
+    /// their mode and options, including the values of sub-codec options. The
+    /// values of sensitive options (e.g. keys) are hidden if `redact` is set.
+    pub fn explain<W: Write + ?Sized>(&self, redact: bool, output: &mut W) -> Result<(), Error> {
+        check_codecs(CodecMetaInfo::instance(), &self.codecs)?;
+
+        writeln!(output, "mode: {}", self.mode)?;
+        writeln!(output, "engine: {}", self.engine)?;
+        writeln!(output, "codecs:")?;
+        explain_codecs(&self.codecs, self.mode, redact, 4, output)
+    }
+
+    fn last_codec(&mut self) -> &mut commands::Codec {
+        self.codecs
+            .last_mut()
+            .expect("an option must follow a codec")
+    }
+}
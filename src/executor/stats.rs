@@ -0,0 +1,259 @@
+use std::{
+    io::{Read, Write},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{executor::Engine, utils::json_string};
+
+/// How `--stats` are reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum StatsFormat {
+    Text,
+    Json,
+}
+
+/// Bytes and time of a codec (stage) of a chain, collected by
+/// [`Pipeline::run_with_stats`](super::Pipeline::run_with_stats).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StageStats {
+    pub name: String,
+    /// Bytes read from the input.
+    pub bytes_in: u64,
+    /// Bytes written to the output.
+    pub bytes_out: u64,
+    /// From the start to the end of the codec.
+    pub wall: Duration,
+    /// Wall time minus the time spent waiting to read the input or to write
+    /// the output, i.e. the time the codec itself took. The stage with the
+    /// longest busy time is the bottleneck of the chain.
+    pub busy: Duration,
+}
+
+impl StageStats {
+    /// Bytes of input processed per second of busy time.
+    pub fn throughput(&self) -> Option<f64> {
+        let busy = self.busy.as_secs_f64();
+        (busy > 0.0).then(|| self.bytes_in as f64 / busy)
+    }
+}
+
+/// Counters of a stage, shared with the thread running it.
+#[derive(Default)]
+pub(super) struct StageCounters {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    wait_nanos: AtomicU64,
+    wall_nanos: AtomicU64,
+}
+
+impl StageCounters {
+    /// Wraps the input of the stage, counting the bytes read and the time
+    /// spent reading them.
+    pub(super) fn reader<R>(&self, inner: R) -> Counting<'_, R> {
+        Counting {
+            inner,
+            bytes: &self.bytes_in,
+            wait_nanos: &self.wait_nanos,
+        }
+    }
+
+    /// Wraps the output of the stage, counting the bytes written and the time
+    /// spent writing them.
+    pub(super) fn writer<W>(&self, inner: W) -> Counting<'_, W> {
+        Counting {
+            inner,
+            bytes: &self.bytes_out,
+            wait_nanos: &self.wait_nanos,
+        }
+    }
+
+    /// Runs the stage, measuring its wall time.
+    pub(super) fn time<T>(&self, run: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = run();
+        self.wall_nanos
+            .store(nanos(start.elapsed()), Ordering::Relaxed);
+        result
+    }
+
+    pub(super) fn to_stats(&self, name: &str) -> StageStats {
+        let wall = Duration::from_nanos(self.wall_nanos.load(Ordering::Relaxed));
+        let wait = Duration::from_nanos(self.wait_nanos.load(Ordering::Relaxed));
+        StageStats {
+            name: name.to_string(),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            wall,
+            busy: wall.saturating_sub(wait),
+        }
+    }
+}
+
+fn nanos(duration: Duration) -> u64 {
+    duration.as_nanos().try_into().unwrap_or(u64::MAX)
+}
+
+pub(super) struct Counting<'a, T> {
+    inner: T,
+    bytes: &'a AtomicU64,
+    wait_nanos: &'a AtomicU64,
+}
+
+impl<T> Counting<'_, T> {
+    fn add(&self, start: Instant, result: &std::io::Result<usize>) {
+        self.wait_nanos
+            .fetch_add(nanos(start.elapsed()), Ordering::Relaxed);
+        if let Ok(n) = result {
+            self.bytes.fetch_add(*n as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<T: Read> Read for Counting<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let start = Instant::now();
+        let result = self.inner.read(buf);
+        self.add(start, &result);
+        result
+    }
+}
+
+impl<T: Write> Write for Counting<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let start = Instant::now();
+        let result = self.inner.write(buf);
+        self.add(start, &result);
+        result
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let start = Instant::now();
+        let result = self.inner.flush();
+        self.add(start, &Ok(0));
+        result
+    }
+}
+
+pub(super) fn report_stats(
+    output: &mut dyn Write,
+    format: StatsFormat,
+    stats: &[StageStats],
+    engine: Engine,
+    wall: Duration,
+) -> std::io::Result<()> {
+    match format {
+        StatsFormat::Text => write_stats(output, stats, engine, wall),
+        StatsFormat::Json => write_stats_json(output, stats, engine, wall),
+    }
+}
+
+/// Writes `stats` as a table, e.g.
+///
+/// ```text
+/// stats: 2 codecs, buffered engine, 1.204s
+///   # codec        in         out        wall      busy  throughput
+///   1 zlib      100.0MiB     1.2MiB    1.204s    1.150s  87.0MiB/s
+///   2 base64      1.2MiB     1.6MiB    1.204s    0.010s  120.0MiB/s
+/// ```
+fn write_stats(
+    output: &mut dyn Write,
+    stats: &[StageStats],
+    engine: Engine,
+    wall: Duration,
+) -> std::io::Result<()> {
+    let name_width = stats
+        .iter()
+        .map(|stage| stage.name.len())
+        .chain(Some("codec".len()))
+        .max()
+        .unwrap_or_default();
+
+    writeln!(
+        output,
+        "stats: {} codecs, {} engine, {:.3}s",
+        stats.len(),
+        engine,
+        wall.as_secs_f64()
+    )?;
+    writeln!(
+        output,
+        "  {:>2} {:<name_width$} {:>10} {:>10} {:>9} {:>9} {:>11}",
+        "#", "codec", "in", "out", "wall", "busy", "throughput"
+    )?;
+    for (index, stage) in stats.iter().enumerate() {
+        let throughput = match stage.throughput() {
+            Some(throughput) => format!("{}/s", format_bytes(throughput)),
+            None => "-".to_string(),
+        };
+        writeln!(
+            output,
+            "  {:>2} {:<name_width$} {:>10} {:>10} {:>8.3}s {:>8.3}s {:>11}",
+            index + 1,
+            stage.name,
+            format_bytes(stage.bytes_in as f64),
+            format_bytes(stage.bytes_out as f64),
+            stage.wall.as_secs_f64(),
+            stage.busy.as_secs_f64(),
+            throughput
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes `stats` as a single line of JSON, e.g.
+///
+/// ```text
+/// {"engine":"buffered","wall_ns":1204000000,"stages":[{"codec":"zlib",
+/// "bytes_in":104857600,"bytes_out":1258291,"wall_ns":1204000000,
+/// "busy_ns":1150000000,"throughput":91180521.7}, ...]}
+/// ```
+///
+/// `throughput` (bytes per second) is `null` if the busy time is 0.
+fn write_stats_json(
+    output: &mut dyn Write,
+    stats: &[StageStats],
+    engine: Engine,
+    wall: Duration,
+) -> std::io::Result<()> {
+    write!(
+        output,
+        "{{\"engine\":\"{}\",\"wall_ns\":{},\"stages\":[",
+        engine,
+        wall.as_nanos()
+    )?;
+    for (index, stage) in stats.iter().enumerate() {
+        if index > 0 {
+            write!(output, ",")?;
+        }
+        let throughput = match stage.throughput() {
+            Some(throughput) => format!("{:.1}", throughput),
+            None => "null".to_string(),
+        };
+        write!(
+            output,
+            "{{\"codec\":{},\"bytes_in\":{},\"bytes_out\":{},\"wall_ns\":{},\"busy_ns\":{},\"throughput\":{}}}",
+            json_string(&stage.name),
+            stage.bytes_in,
+            stage.bytes_out,
+            stage.wall.as_nanos(),
+            stage.busy.as_nanos(),
+            throughput
+        )?;
+    }
+    writeln!(output, "]}}")
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024.0 {
+        return format!("{}B", bytes as u64);
+    }
+    let mut value = bytes / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
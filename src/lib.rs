@@ -0,0 +1,29 @@
+//! A Swiss Army knife for en/de- coding/crypting strings.
+//!
+//! The engine behind the `codec` binary. A chain of codecs is described by a
+//! [`Pipeline`], either parsed from command line arguments or built by hand,
+//! and can be run over any [`Read`](std::io::Read)/[`Write`](std::io::Write)
+//! pair:
+//!
+//! ```no_run
+//! use codec_rust::{CodecMode, Pipeline};
+//!
+//! let mut output = Vec::new();
+//! Pipeline::new()
+//!     .mode(CodecMode::Decoding)
+//!     .codec("base64")
+//!     .codec("zlib")
+//!     .run(std::io::stdin(), &mut output)?;
+//! # Ok::<(), codec_rust::Error>(())
+//! ```
+
+pub mod codecs;
+pub mod completions;
+pub mod executor;
+#[cfg(feature = "repl")]
+pub mod repl;
+pub mod self_test;
+mod utils;
+
+pub use codecs::{load_builtins, Codec, CodecMetaInfo, CodecMode, CodecUsage, MetaCodec, Options};
+pub use executor::{Engine, Error, ParseError, Pipeline, StageStats};
@@ -1,68 +1,89 @@
-mod codecs;
-mod executor;
-mod utils;
-
-use codecs::{builtins::*, meta::UsageMetaCodec, CodecMetaInfo};
+use codec_rust::{
+    codecs::meta,
+    completions::{write_completions, Shell},
+    load_builtins, CodecMetaInfo, Pipeline,
+};
 
 fn main() {
-    let codecs = load_builtins();
+    let mut codecs = load_builtins();
+    if let Some(path) = meta::default_aliases_path() {
+        meta::load_aliases(&mut codecs, &path).unwrap_or_else(|err| {
+            eprintln!("Error when loading aliases from {}: {}", path.display(), err);
+            std::process::exit(1)
+        });
+    }
+    codec_rust::codecs::plugin::load_plugins(
+        &mut codecs,
+        &codec_rust::codecs::plugin::default_plugin_dirs(),
+    );
     CodecMetaInfo::set_instance(codecs);
 
     let args: Vec<_> = std::env::args().skip(1).collect();
-    let mut tokenizer = executor::parser::Tokenizer::new(args);
-
-    // TODO: eliminate unwrap
-
-    let commands = executor::parser::parse_command(&mut tokenizer).unwrap_or_else(|err| {
+    if args.first().map(String::as_str) == Some("--completions") {
+        completions(&args[1..]).unwrap_or_else(|err| {
+            eprintln!("Error when generating completions: {}", err);
+            std::process::exit(1)
+        });
+        return;
+    }
+    if args.first().map(String::as_str) == Some("--self-test") {
+        let failed =
+            codec_rust::self_test::run_self_test(&mut std::io::stdout(), CodecMetaInfo::instance())
+                .unwrap_or_else(|err| {
+                    eprintln!("Error in self test: {}", err);
+                    std::process::exit(1)
+                });
+        if failed > 0 {
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("--repl") {
+        repl(&args[1..]).unwrap_or_else(|err| {
+            eprintln!("Error in repl: {}", err);
+            std::process::exit(1)
+        });
+        return;
+    }
+    let pipeline = Pipeline::parse(args).unwrap_or_else(|err| {
         eprintln!("Error when parsing command: {}", err);
         std::process::exit(1)
     });
 
-    executor::execute(commands).unwrap_or_else(|err| {
-        eprintln!("Error in executing: {}", err);
-        std::process::exit(1)
-    });
+    pipeline
+        .run(std::io::stdin(), &mut std::io::stdout())
+        .unwrap_or_else(|err| {
+            eprintln!("Error in executing: {}", err);
+            std::process::exit(1)
+        });
 }
 
-fn load_builtins() -> codecs::CodecMetaInfo {
-    let mut meta_info = codecs::CodecMetaInfo::new();
-    meta_info.register_codec::<IdCodecs>("id");
-    meta_info.register_codec::<ConstCodecs>("const");
-    meta_info.register_codec::<RepeatCodecs>("repeat");
-    meta_info.register_codec::<AppendCodecs>("append");
-    meta_info.register_codec::<NewLineCodecs>("newline");
-    meta_info.register_codec::<CatCodecs>("cat");
-    meta_info.register_codec::<DropCodecs>("drop");
-    meta_info.register_codec::<TakeCodecs>("take");
-    meta_info.register_codec::<TeeCodecs>("tee");
-    meta_info.register_codec::<SinkCodecs>("sink");
-    meta_info.register_codec::<RedirectCodecs>("redirect");
-    meta_info.register_codec::<Base64Codec>("base64");
-    meta_info.register_codec::<HexCodec>("hex");
-    #[cfg(feature = "libc")]
-    meta_info.register("aes-cbc", AesCodec::new_cbc());
-    #[cfg(feature = "libc")]
-    meta_info.register("aes-ecb", AesCodec::new_ecb());
-    #[cfg(feature = "libc")]
-    meta_info.register("sm4-cbc", Sm4Codec::new_cbc());
-    #[cfg(feature = "libc")]
-    meta_info.register("sm4-ecb", Sm4Codec::new_ecb());
-    meta_info.register("md5", HashCodec::new_md5());
-    #[cfg(feature = "libc")]
-    meta_info.register("sha256", HashCodec::new_sha256());
-    meta_info.register("sm3", HashCodec::new_sm3());
-    meta_info.register_codec::<UrlCodec>("url");
-    #[cfg(feature = "libc")]
-    meta_info.register_codec::<ZlibCodec>("zlib");
-    meta_info.register_codec::<EscapeCodec>("escape");
-    #[cfg(feature = "libc")]
-    meta_info.register_codec::<RsaCryptCodec>("rsa-crypt");
-    #[cfg(feature = "libc")]
-    meta_info.register_codec::<RsaSignCodec>("rsa-sign");
+/// `codec --completions shell`: prints the completion script of `shell`.
+fn completions(args: &[String]) -> anyhow::Result<()> {
+    let [shell] = args else {
+        anyhow::bail!("expect a shell, one of {}", Shell::NAMES.join(", "));
+    };
+    write_completions(
+        &mut std::io::stdout(),
+        shell.parse()?,
+        CodecMetaInfo::instance(),
+        Pipeline::options(),
+    )
+}
 
-    #[cfg(feature = "system")]
-    meta_info.register_codec::<SystemCodec>("system");
+/// `codec --repl [file]`: starts the REPL on the content of `file`, or on an
+/// empty buffer.
+#[cfg(feature = "repl")]
+fn repl(args: &[String]) -> anyhow::Result<()> {
+    let buffer = match args {
+        [] => vec![],
+        [path] => std::fs::read(path).map_err(|err| anyhow::anyhow!("{}: {}", path, err))?,
+        _ => anyhow::bail!("expect at most one file"),
+    };
+    codec_rust::repl::run(buffer)
+}
 
-    meta_info.register_meta("usage", UsageMetaCodec);
-    meta_info
+#[cfg(not(feature = "repl"))]
+fn repl(_args: &[String]) -> anyhow::Result<()> {
+    anyhow::bail!("not supported, rebuild with feature `repl`")
 }
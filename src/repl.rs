@@ -0,0 +1,308 @@
+//! Interactive mode (`codec --repl`): chains typed at the prompt are run on a
+//! current buffer, which is replaced by their output.
+//!
+//! ```text
+//! codec> base64
+//! aGVsbG8=
+//! codec> -d base64 zlib
+//! 00000000  78 9c cb 48 cd c9 c9 07  00 06 2c 02 15           |x..H......,..|
+//! 0000000d
+//! codec> :undo
+//! ```
+
+use rustyline::{
+    completion::{Completer, FilenameCompleter, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    CompletionType, Config, Context, Editor, Helper,
+};
+
+use crate::{
+    codecs::{CodecMetaInfo, OptionSpec},
+    executor::mode_options,
+    Pipeline,
+};
+
+const PROMPT: &str = "codec> ";
+const COMMAND_PREFIX: char = ':';
+const COMMANDS: &[&str] = &["help", "show", "view", "undo", "load", "save", "quit"];
+const VIEWS: &[&str] = &["auto", "text", "hex"];
+
+const HELP: &str = "\
+chain: run the chain on the buffer, e.g. `-d base64 zlib`, and replace the
+    buffer with its output (the chain accepts the global options of `codec`)
+:show: print the buffer
+:view auto|text|hex: print the buffer as text, as a hexdump, or as text if it
+    is printable (default)
+:undo: restore the buffer before the last change
+:load file: replace the buffer with the content of `file`
+:save file: write the buffer to `file`
+:quit: exit (or Ctrl-D)";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum View {
+    Auto,
+    Text,
+    Hex,
+}
+
+/// Runs the REPL on stdin/stdout, starting with `buffer`.
+pub fn run(buffer: Vec<u8>) -> anyhow::Result<()> {
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+    let mut editor = Editor::with_config(config)?;
+    editor.set_helper(Some(ReplHelper {
+        files: FilenameCompleter::new(),
+    }));
+
+    let mut repl = Repl {
+        buffer,
+        undo: vec![],
+        view: View::Auto,
+    };
+    repl.show();
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line)?;
+
+        let result = match line.strip_prefix(COMMAND_PREFIX) {
+            Some(command) => match repl.command(command) {
+                Ok(false) => break,
+                result => result.map(|_| ()),
+            },
+            None => repl.run_chain(line),
+        };
+        if let Err(err) = result {
+            eprintln!("error: {}", err);
+        }
+    }
+    Ok(())
+}
+
+struct Repl {
+    buffer: Vec<u8>,
+    /// Previous buffers, the last one first restored by `:undo`.
+    undo: Vec<Vec<u8>>,
+    view: View,
+}
+
+impl Repl {
+    fn run_chain(&mut self, line: &str) -> anyhow::Result<()> {
+        let pipeline = Pipeline::parse_str(line)?;
+        let mut output = vec![];
+        pipeline.run(self.buffer.as_slice(), &mut output)?;
+        self.replace(output);
+        Ok(())
+    }
+
+    /// Runs a `:command`, returns `false` to exit.
+    fn command(&mut self, command: &str) -> anyhow::Result<bool> {
+        let (name, argument) = match command.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
+
+        match name {
+            "help" => println!("{}", HELP),
+            "show" => self.show(),
+            "view" => {
+                self.view = match argument {
+                    "auto" => View::Auto,
+                    "text" => View::Text,
+                    "hex" => View::Hex,
+                    _ => anyhow::bail!("expect one of {}", VIEWS.join(", ")),
+                };
+                self.show();
+            }
+            "undo" => {
+                let buffer = self
+                    .undo
+                    .pop()
+                    .ok_or_else(|| anyhow::anyhow!("nothing to undo"))?;
+                self.buffer = buffer;
+                self.show();
+            }
+            "load" => {
+                let buffer = std::fs::read(expect_file(argument)?)
+                    .map_err(|err| anyhow::anyhow!("{}: {}", argument, err))?;
+                self.replace(buffer);
+            }
+            "save" => {
+                std::fs::write(expect_file(argument)?, &self.buffer)
+                    .map_err(|err| anyhow::anyhow!("{}: {}", argument, err))?;
+                println!("{} bytes written to {}", self.buffer.len(), argument);
+            }
+            "quit" => return Ok(false),
+            _ => anyhow::bail!("unknown command `:{}`, see `:help`", name),
+        }
+        Ok(true)
+    }
+
+    fn replace(&mut self, buffer: Vec<u8>) {
+        self.undo.push(std::mem::replace(&mut self.buffer, buffer));
+        self.show();
+    }
+
+    fn show(&self) {
+        let as_text = match self.view {
+            View::Auto => is_printable(&self.buffer),
+            View::Text => true,
+            View::Hex => false,
+        };
+
+        if as_text {
+            let text = String::from_utf8_lossy(&self.buffer);
+            if text.ends_with('\n') {
+                print!("{}", text);
+            } else {
+                println!("{}", text);
+            }
+        } else {
+            print!("{}", hexdump(&self.buffer));
+        }
+    }
+}
+
+fn expect_file(argument: &str) -> anyhow::Result<&str> {
+    if argument.is_empty() {
+        anyhow::bail!("expect a file name");
+    }
+    Ok(argument)
+}
+
+fn is_printable(buffer: &[u8]) -> bool {
+    std::str::from_utf8(buffer).is_ok_and(|text| {
+        text.chars()
+            .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+    })
+}
+
+/// `hexdump -C` like lines: offset, 16 bytes in hex and as ASCII.
+fn hexdump(buffer: &[u8]) -> String {
+    let mut result = String::new();
+    for (index, line) in buffer.chunks(16).enumerate() {
+        result.push_str(&format!("{:08x} ", index * 16));
+        for i in 0..16 {
+            if i % 8 == 0 {
+                result.push(' ');
+            }
+            match line.get(i) {
+                Some(byte) => result.push_str(&format!("{:02x} ", byte)),
+                None => result.push_str("   "),
+            }
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        result.push_str(&format!(" |{}|\n", ascii));
+    }
+    result.push_str(&format!("{:08x}\n", buffer.len()));
+    result
+}
+
+/// Completes `:commands`, file names after `:load`/`:save`, codec names, and
+/// option names of the codec before the cursor (or global options before any
+/// codec).
+struct ReplHelper {
+    files: FilenameCompleter,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+
+        if let Some(command) = before.strip_prefix(COMMAND_PREFIX) {
+            return match command.split_once(char::is_whitespace) {
+                None => Ok((1, candidates(COMMANDS.iter().copied(), command))),
+                Some(("load" | "save", _)) => self.files.complete(line, pos, ctx),
+                Some(("view", argument)) => Ok((
+                    before.len() - argument.len(),
+                    candidates(VIEWS.iter().copied(), argument),
+                )),
+                Some(_) => Ok((pos, vec![])),
+            };
+        }
+
+        let start = before
+            .rfind(|c: char| c.is_whitespace() || c == '[')
+            .map_or(0, |i| i + 1);
+        let word = &before[start..];
+        let meta_info = CodecMetaInfo::instance();
+
+        if !word.starts_with('-') {
+            let names = meta_info.codecs_iter().map(|(name, _)| name.as_str());
+            return Ok((start, candidates(names, word)));
+        }
+
+        // the last word naming a codec, if any, is the codec being written
+        let codec = before[..start]
+            .split(|c: char| c.is_whitespace() || c == '[' || c == ']')
+            .rev()
+            .find_map(|word| meta_info.lookup(word));
+        let specs: Vec<OptionSpec> = match codec {
+            Some(codec) => {
+                let mut specs = codec.options().unwrap_or_default();
+                specs.extend(mode_options());
+                specs
+            }
+            None => Pipeline::options(),
+        };
+        let names: Vec<String> = specs
+            .iter()
+            .flat_map(|spec| {
+                std::iter::once(format!("-{}", spec.name))
+                    .chain(spec.long.map(|long| format!("--{}", long)))
+            })
+            .collect();
+        Ok((start, candidates(names.iter().map(String::as_str), word)))
+    }
+}
+
+fn candidates<'a>(names: impl Iterator<Item = &'a str>, prefix: &str) -> Vec<Pair> {
+    let mut candidates: Vec<Pair> = names
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| Pair {
+            display: name.to_string(),
+            replacement: name.to_string(),
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.display.cmp(&b.display));
+    candidates
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
@@ -0,0 +1,51 @@
+//! `codec --self-test`: runs the examples declared by the codecs (see
+//! [`Codec::examples`](crate::Codec::examples)) and checks their output.
+
+use std::io::Write;
+
+use crate::{codecs::CodecMetaInfo, Pipeline};
+
+/// Runs every example of the codecs of `meta_info`, writing one line per
+/// example and a summary to `output`. Returns the number of failed examples.
+pub fn run_self_test(output: &mut dyn Write, meta_info: &CodecMetaInfo) -> anyhow::Result<usize> {
+    let mut codecs: Vec<_> = meta_info.codecs_iter().collect();
+    codecs.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut total = 0;
+    let mut failed = 0;
+    for (name, codec) in codecs {
+        for example in codec.examples() {
+            total += 1;
+            match run_example(example.command, example.input) {
+                Ok(actual) if actual == example.output.as_bytes() => {
+                    writeln!(output, "ok      {}: {}", name, example.command)?;
+                }
+                Ok(actual) => {
+                    failed += 1;
+                    writeln!(output, "FAIL    {}: {}", name, example.command)?;
+                    writeln!(output, "    input:    {:?}", example.input)?;
+                    writeln!(output, "    expected: {:?}", example.output)?;
+                    writeln!(
+                        output,
+                        "    actual:   {:?}",
+                        String::from_utf8_lossy(&actual)
+                    )?;
+                }
+                Err(err) => {
+                    failed += 1;
+                    writeln!(output, "FAIL    {}: {}", name, example.command)?;
+                    writeln!(output, "    input:    {:?}", example.input)?;
+                    writeln!(output, "    error:    {}", err)?;
+                }
+            }
+        }
+    }
+    writeln!(output, "{} examples, {} failed", total, failed)?;
+    Ok(failed)
+}
+
+fn run_example(command: &str, input: &str) -> anyhow::Result<Vec<u8>> {
+    let mut output = vec![];
+    Pipeline::parse_str(command)?.run(input.as_bytes(), &mut output)?;
+    Ok(output)
+}
@@ -250,3 +250,59 @@ where
         Ok(n)
     }
 }
+
+/// Quotes `value` as a JSON string.
+pub fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Quotes `word` for the shell, e.g. `it's` as `'it'\''s'`.
+pub fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Reader skipping the ASCII whitespaces of `reader`, e.g. the line breaks of
+/// PEM or MIME bodies.
+pub struct SkipWhitespace<R> {
+    reader: R,
+}
+
+impl<R: std::io::Read> SkipWhitespace<R> {
+    pub fn new(reader: R) -> Self {
+        SkipWhitespace { reader }
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for SkipWhitespace<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let n = self.reader.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+
+            let mut length = 0;
+            for i in 0..n {
+                if !buf[i].is_ascii_whitespace() {
+                    buf[length] = buf[i];
+                    length += 1;
+                }
+            }
+            // only whitespaces read, not the end of input yet
+            if length > 0 {
+                return Ok(length);
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::{path::PathBuf, sync::Once};
+
+use codec_rust::{codecs::meta, load_builtins, CodecMetaInfo, Error, Pipeline};
+
+/// Writes `content` to a file of the temporary directory, unique to this test
+/// process.
+fn aliases_file(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("codec-aliases-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+/// Registers the aliases of the tests in the global registry.
+fn setup() {
+    static SETUP: Once = Once::new();
+    SETUP.call_once(|| {
+        let path = aliases_file(
+            "global",
+            "# comment\n\
+             zb = hex base64\n\
+             greet = append -A $S\n\
+             price = append -A $$5\n\
+             nested = greet -S x hex\n",
+        );
+        let mut codecs = load_builtins();
+        meta::load_aliases(&mut codecs, &path).unwrap();
+        CodecMetaInfo::set_instance(codecs);
+    });
+}
+
+fn run(args: &[&str], input: &str) -> Result<String, Error> {
+    setup();
+    let mut output = Vec::new();
+    Pipeline::parse(args.iter().copied())?.run(input.as_bytes(), &mut output)?;
+    Ok(String::from_utf8(output).expect("output is not utf-8"))
+}
+
+fn load_error(content: &str) -> String {
+    let path = aliases_file("error", content);
+    let mut codecs = load_builtins();
+    let result = meta::load_aliases(&mut codecs, &path);
+    std::fs::remove_file(path).unwrap();
+    match result {
+        Err(err) => err.to_string(),
+        Ok(()) => panic!("expect an error loading {:?}", content),
+    }
+}
+
+#[test]
+fn expansion() {
+    assert_eq!(run(&["zb"], "hi").unwrap(), "Njg2OQ==");
+    assert!(run(&["-x", "zb"], "")
+        .unwrap()
+        .contains("1. zb (encode) = hex base64\n"));
+}
+
+#[test]
+fn placeholders() {
+    assert_eq!(run(&["greet", "-S", "!"], "hi").unwrap(), "hi!");
+    assert_eq!(run(&["nested"], "hi").unwrap(), "686978");
+    assert_eq!(run(&["price"], "a").unwrap(), "a$5");
+}
+
+#[test]
+fn missing_placeholder_option() {
+    match run(&["greet"], "hi") {
+        Err(Error::InvalidOption {
+            codec,
+            option,
+            reason,
+        }) => {
+            assert_eq!((codec.as_str(), option.as_str()), ("greet", "S"));
+            assert_eq!(reason, "missing required option");
+        }
+        result => panic!("expect an invalid option, found {:?}", result),
+    }
+}
+
+#[test]
+fn cycles() {
+    assert_eq!(
+        load_error("loop = loop hex\n"),
+        "alias `loop` refers to itself"
+    );
+    assert_eq!(
+        load_error("a = append -A [x a]\n"),
+        "alias `a` refers to itself"
+    );
+    assert_eq!(
+        load_error("a = hex b\nb = base64 c\nc = a\n"),
+        "aliases refer to each other: a -> b -> c -> a"
+    );
+
+    // a value named like an alias is not a reference
+    let path = aliases_file("value", "a = append -A a\nb = a a\n");
+    let mut codecs = load_builtins();
+    meta::load_aliases(&mut codecs, &path).unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn invalid_files() {
+    assert_eq!(load_error("zb\n"), "line 1: expect `name = codecs`");
+    assert_eq!(
+        load_error("hex = base64\n"),
+        "line 1: `hex` is already defined"
+    );
+}
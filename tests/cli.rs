@@ -0,0 +1,52 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Runs the `codec` binary on `input`, without the aliases and plugins of the
+/// user.
+fn codec(args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_codec-rust"))
+        .args(args)
+        .env("CODEC_ALIASES", "/nonexistent")
+        .env("CODEC_PLUGIN_PATH", "/nonexistent")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    std::thread::scope(|scope| {
+        // written while the output is read, which may be larger than the pipe
+        scope.spawn(move || stdin.write_all(input).unwrap());
+        child.wait_with_output().unwrap()
+    })
+}
+
+#[test]
+fn stats_json() {
+    let input: Vec<u8> = (0..=255u8).cycle().take(100_000).collect();
+    for engine in ["threaded", "buffered", "sequential"] {
+        let output = codec(
+            &["-j", "-E", engine, "hex", "drop", "-B", "1000", "base64"],
+            &input,
+        );
+        assert!(output.status.success(), "{:?}", output);
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let stats: serde_json::Value = serde_json::from_str(&stderr)
+            .unwrap_or_else(|err| panic!("invalid JSON {:?}: {}", stderr, err));
+        assert_eq!(stats["engine"], engine);
+
+        let stages = stats["stages"].as_array().unwrap();
+        let bytes = |index: usize, key: &str| stages[index][key].as_u64().unwrap();
+        let names: Vec<_> = stages.iter().map(|stage| &stage["codec"]).collect();
+        assert_eq!(names, ["hex", "drop", "base64"]);
+        assert_eq!(bytes(0, "bytes_in"), input.len() as u64);
+        for index in 1..stages.len() {
+            assert_eq!(bytes(index, "bytes_in"), bytes(index - 1, "bytes_out"));
+        }
+        assert_eq!(bytes(1, "bytes_out"), bytes(1, "bytes_in") - 1000);
+        assert_eq!(bytes(2, "bytes_out"), output.stdout.len() as u64);
+    }
+}
@@ -0,0 +1,260 @@
+use codec_rust::{self_test, CodecMetaInfo, Error, Pipeline};
+
+fn run(args: &[&str], input: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    Pipeline::parse(args.iter().copied())?.run(input, &mut output)?;
+    Ok(output)
+}
+
+fn assert_output(args: &[&str], input: &[u8], expected: &[u8]) {
+    match run(args, input) {
+        Ok(output) => assert_eq!(output, expected, "args: {:?}", args),
+        Err(err) => panic!("args: {:?}: {}", args, err),
+    }
+}
+
+/// The error of the failing codec.
+fn codec_error(args: &[&str], input: &[u8]) -> String {
+    match run(args, input) {
+        Err(Error::Codec { source, .. }) => source.to_string(),
+        Err(err) => panic!("args: {:?}: expect a codec error, found {}", args, err),
+        Ok(output) => panic!("args: {:?}: expect a codec error, found {:?}", args, output),
+    }
+}
+
+#[test]
+fn basex_trailing_whitespaces() {
+    assert_output(&["base58", "-d"], b"StV1DL6CwTryKyV\n", b"hello world");
+    assert_output(&["base62", "-d"], b"AAwf93rvy4aWQVw\r\n", b"hello world");
+    assert_output(&["base36", "-d"], b"fuvrsivvnfrbjwajo \t\n", b"hello world");
+    assert_eq!(
+        codec_error(&["base58", "-d"], b" StV1DL6CwTryKyV"),
+        "base58: invalid symbol ` ` at position 0"
+    );
+}
+
+#[test]
+fn basex_leading_zeros() {
+    assert_output(&["base58"], b"\0\0\0a", b"1112g");
+    assert_output(&["base58", "-d"], b"1112g", b"\0\0\0a");
+    assert_output(&["base58", "-d"], b"11", b"\0\0");
+    assert_output(&["base62"], b"\0\0a", b"001Z");
+    assert_output(&["base62", "-d"], b"001Z", b"\0\0a");
+    assert_output(&["base36"], b"\0\0a", b"002p");
+    assert_output(&["base36", "-d"], b"002P", b"\0\0a");
+    assert_output(&["base58"], b"", b"");
+    assert_output(&["base58", "-d"], b"", b"");
+}
+
+#[cfg(feature = "libc")]
+#[test]
+fn base58check_errors() {
+    assert_output(
+        &["base58check", "-d", "hex"],
+        b"16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvM\n",
+        b"00010966776006953d5567439e5e39f86a0d273bee",
+    );
+    assert_eq!(
+        codec_error(&["base58check", "-d"], b"16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvN"),
+        "base58check: checksum mismatch, expect d61967f6, found d61967f7"
+    );
+    assert_eq!(
+        codec_error(&["base58check", "-d"], b"1"),
+        "base58check: 1 byte(s) decoded, shorter than the checksum"
+    );
+    // leading zero bytes are kept in the payload and the checksum
+    assert_output(&["base58check", "base58check", "-d"], b"\0\0a", b"\0\0a");
+}
+
+#[test]
+fn self_test_examples_pass() {
+    let mut output = Vec::new();
+    let failed = self_test::run_self_test(&mut output, CodecMetaInfo::instance()).unwrap();
+    assert_eq!(failed, 0, "{}", String::from_utf8_lossy(&output));
+}
+
+/// Bytes of every value, long enough for the encoded text to span several
+/// reads of the decoders.
+fn binary_input() -> Vec<u8> {
+    (0..=255u8).cycle().take(3000).collect()
+}
+
+#[test]
+fn base32_across_reads() {
+    let input = binary_input();
+    for alphabet in ["standard", "hex", "crockford", "z-base-32"] {
+        let encoded = run(&["base32", "-A", alphabet], &input).unwrap();
+        assert_output(&["base32", "-d", "-A", alphabet], &encoded, &input);
+    }
+
+    // hyphens shift the groups of 8 symbols across the reads
+    let encoded = run(&["base32", "-A", "crockford"], &input).unwrap();
+    let hyphenated: Vec<u8> = encoded
+        .chunks(3)
+        .flat_map(|chunk| chunk.iter().copied().chain([b'-']))
+        .collect();
+    assert_output(&["base32", "-d", "-A", "crockford"], &hyphenated, &input);
+}
+
+#[test]
+fn base32_errors() {
+    assert_output(&["base32", "-d"], b"NBUQ", b"hi");
+    assert_output(&["base32", "-d"], b"NBSWY3DP\n", b"hello");
+    assert_output(&["base32", "-d"], b"NBSW\r\nY3DP ", b"hello");
+    assert_eq!(
+        codec_error(&["base32", "-d"], b"NBSW1==="),
+        "invalid base32 symbol `1`"
+    );
+    assert_eq!(
+        codec_error(&["base32", "-d"], b"NBUQ====NBUQ===="),
+        "base32 symbol after padding"
+    );
+    assert_eq!(
+        codec_error(&["base32", "-d"], b"NBU"),
+        "invalid base32 length, 3 symbol(s) left"
+    );
+    assert_eq!(
+        codec_error(&["base32", "-d", "-A", "hex"], b"W"),
+        "invalid base32 symbol `W`"
+    );
+}
+
+#[test]
+fn base64_wrapping() {
+    assert_output(&["base64", "-w", "4"], b"hello", b"aGVs\nbG8=\n");
+    assert_output(&["base64", "-d"], b"aGVs\nbG8=\n", b"hello");
+    match run(&["base64", "-w", "-1"], b"hello") {
+        Err(Error::InvalidOption { reason, .. }) => {
+            assert_eq!(reason, "expect int >= 0, found `-1`")
+        }
+        result => panic!("expect an invalid option, found {:?}", result),
+    }
+}
+
+#[test]
+fn base85_across_reads() {
+    let mut input = vec![0; 8];
+    input.extend(binary_input());
+    input.extend([0; 12]);
+    for variant in ["ascii85", "z85", "rfc1924"] {
+        let encoded = run(&["base85", "-V", variant], &input).unwrap();
+        assert_output(&["base85", "-d", "-V", variant], &encoded, &input);
+    }
+
+    // line breaks shift the groups of 5 characters across the reads
+    let encoded = run(&["base85"], &input).unwrap();
+    let wrapped: Vec<u8> = encoded
+        .chunks(7)
+        .flat_map(|chunk| chunk.iter().copied().chain([b'\n']))
+        .collect();
+    assert_output(&["base85", "-d"], &wrapped, &input);
+}
+
+#[test]
+fn base85_errors() {
+    assert_eq!(
+        codec_error(&["base85", "-d"], b"<~BOz~>"),
+        "`z` in the middle of a base85 group"
+    );
+    assert_eq!(
+        codec_error(&["base85", "-d"], b"<~BOu!v~>"),
+        "invalid base85 character `v`"
+    );
+    assert_eq!(
+        codec_error(&["base85", "-d"], b"<~BOu!rDZ~>x"),
+        "data after `~>`"
+    );
+    assert_eq!(
+        codec_error(&["base85", "-d"], b"<~BOu!r~x"),
+        "expect `>` after `~`"
+    );
+    assert_eq!(
+        codec_error(&["base85", "-d"], b"uuuuu"),
+        "base85 group larger than 2^32"
+    );
+    assert_eq!(
+        codec_error(&["base85", "-d"], b"<~BOu!rD~>"),
+        "base85 group of a single character"
+    );
+}
+
+#[test]
+fn z85_lengths() {
+    assert_eq!(
+        codec_error(&["base85", "-V", "z85"], b"hello"),
+        "z85 input length must be a multiple of 4, 1 byte(s) left"
+    );
+    assert_eq!(
+        codec_error(&["base85", "-d", "-V", "z85"], b"HelloWorl"),
+        "z85 input length must be a multiple of 5, 4 character(s) left"
+    );
+    assert_output(&["base85", "-V", "z85"], b"", b"");
+}
+
+#[test]
+fn hex_across_reads() {
+    let input = binary_input();
+    for args in [
+        &["hex"][..],
+        &["hex", "-c"],
+        &["hex", "-x", "-G", "3", "-S", ":"],
+        &["hex", "-F", "c"],
+        &["hex", "-F", "rust"],
+    ] {
+        let encoded = run(args, &input).unwrap();
+        assert_output(&["hex", "-d"], &encoded, &input);
+    }
+
+    // `\x` prefixes cut at every position across the reads
+    let escaped: Vec<u8> = input
+        .iter()
+        .flat_map(|byte| format!("\\x{:02x}", byte).into_bytes())
+        .collect();
+    assert_output(&["hex", "-d"], &escaped, &input);
+}
+
+#[test]
+fn hex_formats() {
+    assert_output(&["hex", "-F", "c"], b"", b"{}");
+    assert_output(&["hex", "-G", "2", "-x"], b"abcde", b"0x6162 0x6364 0x65");
+    assert_output(&["hex", "-d"], b"0X6A0x6b 6C", b"jkl");
+    assert_eq!(
+        codec_error(&["hex", "-F", "rust", "-S", ":"], b"hi"),
+        "hex: -F c and -F rust cannot be used with -S, -G or -x"
+    );
+    assert_eq!(
+        codec_error(&["hex", "-G", "0"], b"hi"),
+        "hex: group size must be positive"
+    );
+}
+
+#[test]
+fn hex_errors() {
+    assert_eq!(
+        codec_error(&["hex", "-d"], b"686"),
+        "odd number of hex digits"
+    );
+    assert_eq!(
+        codec_error(&["hex", "-d"], b"6 8"),
+        "odd number of hex digits"
+    );
+    assert_eq!(
+        codec_error(&["hex", "-d"], b"6g"),
+        "invalid hex character `g`"
+    );
+    assert_eq!(
+        codec_error(&["hex", "-d"], b"\\68"),
+        "invalid hex character `\\\\`"
+    );
+}
+
+#[cfg(feature = "libc")]
+#[test]
+fn rsa_crypt_is_not_invertible() {
+    // decrypting takes the private key instead of the public one
+    match run(&["-r", "rsa-crypt", "-PK", "key", "base64"], b"") {
+        Err(Error::NotInvertible(name)) => assert_eq!(name, "rsa-crypt"),
+        Err(err) => panic!("expect a not invertible error, found {}", err),
+        Ok(output) => panic!("expect a not invertible error, found {:?}", output),
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use codec_rust::{
+    completions::{write_completions, Shell},
+    CodecMetaInfo, Pipeline,
+};
+
+fn completions(shell: Shell) -> String {
+    let mut output = vec![];
+    write_completions(
+        &mut output,
+        shell,
+        CodecMetaInfo::instance(),
+        Pipeline::options(),
+    )
+    .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+/// Checks the syntax of `script` with `shell -n`, unless `shell` is not
+/// installed.
+fn check_syntax(shell: &str, script: &str) {
+    let child = Command::new(shell)
+        .arg("-n")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => panic!("{}: {}", shell, err),
+    };
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{} -n: {}",
+        shell,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn scripts_are_valid() {
+    check_syntax("bash", &completions(Shell::Bash));
+    check_syntax("zsh", &completions(Shell::Zsh));
+    check_syntax("fish", &completions(Shell::Fish));
+}
+
+#[test]
+fn descriptions_are_single_lines() {
+    // the description of `if -o` is written on several lines
+    let zsh = completions(Shell::Zsh);
+    let entry = zsh
+        .lines()
+        .find(|line| line.starts_with("        ('if') options="))
+        .unwrap();
+    assert!(entry.ends_with(") ;;"), "{}", entry);
+    assert!(
+        entry.contains(
+            " '-o:(output) also require the output of the condition chain to be truthy, i.e. not "
+        ),
+        "{}",
+        entry
+    );
+
+    let fish = completions(Shell::Fish);
+    let entry = fish
+        .lines()
+        .find(|line| line.contains("'if'\" -s o -l output -d "))
+        .unwrap();
+    assert!(entry.ends_with('\''), "{}", entry);
+    assert!(fish
+        .lines()
+        .all(|line| line.is_empty() || line.starts_with(['f', 'e', ' '])));
+}
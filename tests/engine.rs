@@ -0,0 +1,175 @@
+use std::{
+    io::{Read, Write},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use codec_rust::{Error, Pipeline};
+
+/// Input yielding `first`, then blocking until `release` receives (or is
+/// dropped) before yielding `second`.
+struct BlockingInput {
+    first: &'static [u8],
+    second: &'static [u8],
+    release: Receiver<()>,
+    reads: usize,
+}
+
+impl Read for BlockingInput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        let part = match self.reads {
+            1 => self.first,
+            2 => {
+                let _ = self.release.recv();
+                self.second
+            }
+            _ => b"",
+        };
+        buf[..part.len()].copy_from_slice(part);
+        Ok(part.len())
+    }
+}
+
+/// Output sending each write through a channel.
+struct ChannelOutput(Sender<Vec<u8>>);
+
+impl Write for ChannelOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let _ = self.0.send(buf.to_vec());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn default_engine_streams_output() {
+    let (release, release_receiver) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel();
+    let pipeline = Pipeline::parse(["hex", "hex", "-d"]).unwrap();
+
+    let runner = thread::spawn(move || {
+        let input = BlockingInput {
+            first: b"hi",
+            second: b"!",
+            release: release_receiver,
+            reads: 0,
+        };
+        pipeline.run(input, &mut ChannelOutput(sender)).unwrap();
+    });
+
+    // the output of the first part arrives while the input is still open
+    let mut output = vec![];
+    while output.len() < 2 {
+        let written = receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("no output before the end of the input");
+        output.extend(written);
+    }
+    assert_eq!(output, b"hi");
+
+    release.send(()).unwrap();
+    runner.join().unwrap();
+    output.extend(receiver.iter().flatten());
+    assert_eq!(output, b"hi!");
+}
+
+#[test]
+fn engines_agree() {
+    // larger than the buffers of the buffered engine
+    let input: Vec<u8> = (0..=255u8).cycle().take(200_000).collect();
+    let chains: &[&[&str]] = &[
+        &[],
+        &["base64"],
+        &["hex", "base32", "base32", "-d", "hex", "-d"],
+        &["drop", "-B", "1000", "take", "-B", "70000", "base85"],
+        &["append", "-A", "[x", "hex]", "repeat", "-T", "2", "hex"],
+        #[cfg(feature = "libc")]
+        &["zlib", "base64", "-w", "76", "base64", "-d", "zlib", "-d"],
+    ];
+
+    for chain in chains {
+        let outputs: Vec<Vec<u8>> = ["threaded", "buffered", "sequential"]
+            .into_iter()
+            .map(|engine| {
+                let args = ["-E", engine].into_iter().chain(chain.iter().copied());
+                let mut output = vec![];
+                Pipeline::parse(args)
+                    .unwrap()
+                    .run(input.as_slice(), &mut output)
+                    .unwrap_or_else(|err| panic!("{} {:?}: {}", engine, chain, err));
+                output
+            })
+            .collect();
+        assert!(!outputs[0].is_empty(), "{:?}", chain);
+        assert!(outputs[0] == outputs[1], "buffered {:?}", chain);
+        assert!(outputs[0] == outputs[2], "sequential {:?}", chain);
+    }
+}
+
+/// The name, index and message of the failing codec, with the output written
+/// before it failed.
+fn codec_error(args: &[&str], input: &[u8]) -> (String, usize, String) {
+    let mut output = vec![];
+    match Pipeline::parse(args.iter().copied())
+        .unwrap()
+        .run(input, &mut output)
+    {
+        Err(Error::Codec {
+            name,
+            index,
+            source,
+        }) => (name, index, source.to_string()),
+        result => panic!("{:?}: expect a codec error, found {:?}", args, result),
+    }
+}
+
+#[test]
+fn threaded_errors() {
+    assert_eq!(
+        codec_error(&["id", "hex", "-d", "id"], b"zz"),
+        (
+            "hex".to_string(),
+            1,
+            "invalid hex character `z`".to_string()
+        )
+    );
+
+    // `take` stops reading: the codecs before it fail with a broken pipe,
+    // which is not an error
+    let input: Vec<u8> = (0..=255u8).cycle().take(1 << 20).collect();
+    let mut output = vec![];
+    Pipeline::parse(["hex", "id", "take", "-B", "4"])
+        .unwrap()
+        .run(input.as_slice(), &mut output)
+        .unwrap();
+    assert_eq!(output, b"0001");
+
+    // both codecs fail on their options, whatever their input: the first one
+    // in the chain is reported
+    assert_eq!(
+        codec_error(&["hex", "-G", "0", "hex", "-F", "c", "-x"], b"hi"),
+        (
+            "hex".to_string(),
+            0,
+            "hex: group size must be positive".to_string()
+        )
+    );
+
+    // the first `hex -d` fails with a broken pipe once the second one has
+    // failed on its output, before reaching the invalid end of its input
+    let mut input = b"7a".repeat(100_000);
+    input.extend(b"gg");
+    assert_eq!(
+        codec_error(&["hex", "-d", "hex", "-d"], &input),
+        (
+            "hex".to_string(),
+            1,
+            "invalid hex character `z`".to_string()
+        )
+    );
+}
@@ -0,0 +1,136 @@
+use std::{
+    io::{Read, Write},
+    sync::Once,
+};
+
+use codec_rust::{load_builtins, CodecMetaInfo, CodecMode, MetaCodec, Options, Pipeline};
+
+/// Upper cases its input.
+struct Shout;
+
+impl MetaCodec for Shout {
+    fn run_meta_codec(
+        &self,
+        input: &mut (dyn Read + Send),
+        _global_mode: CodecMode,
+        _options: &Options,
+        codec_meta_info: &CodecMetaInfo,
+        output: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        assert!(codec_meta_info.lookup("shout").is_some());
+        let mut buffer = vec![];
+        input.read_to_end(&mut buffer)?;
+        output.write_all(&buffer.to_ascii_uppercase())?;
+        Ok(())
+    }
+
+    fn expansion(&self, _options: &Options) -> Option<String> {
+        Some("upper".to_string())
+    }
+
+    fn modes(&self) -> &'static [CodecMode] {
+        &[CodecMode::Encoding]
+    }
+
+    fn feature(&self) -> Option<&'static str> {
+        Some("shout")
+    }
+}
+
+fn setup() {
+    static SETUP: Once = Once::new();
+    SETUP.call_once(|| {
+        let mut codecs = load_builtins();
+        codecs.register_meta("shout", Shout);
+        CodecMetaInfo::set_instance(codecs);
+    });
+}
+
+#[test]
+fn meta_codecs_are_described_like_codecs() {
+    setup();
+    let shout = CodecMetaInfo::instance().lookup("shout").unwrap();
+    assert_eq!(
+        shout.expansion(&Options::default()).as_deref(),
+        Some("upper")
+    );
+    assert_eq!(shout.modes(), &[CodecMode::Encoding]);
+    assert_eq!(shout.feature(), Some("shout"));
+}
+
+#[test]
+fn parse_and_run_in_memory() {
+    setup();
+    let mut output = vec![];
+    Pipeline::parse(["shout", "hex"])
+        .unwrap()
+        .run(&b"hi"[..], &mut output)
+        .unwrap();
+    assert_eq!(output, b"4849");
+
+    let mut output = vec![];
+    Pipeline::new()
+        .codec("append")
+        .text("A", b"!")
+        .codec("shout")
+        .run(&b"hi"[..], &mut output)
+        .unwrap();
+    assert_eq!(output, b"HI!");
+}
+
+#[test]
+fn explain() {
+    setup();
+    let mut output = vec![];
+    Pipeline::parse(["-d", "-I", "aGk=", "base64", "shout", "-e"])
+        .unwrap()
+        .explain(false, &mut output)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "mode: decode\n\
+         engine: threaded\n\
+         codecs:\n    \
+             1. const (decode)\n        \
+                 -C \"aGk=\"\n    \
+             2. base64 (decode)\n    \
+             3. shout (encode) = upper\n"
+    );
+}
+
+#[cfg(feature = "libc")]
+#[test]
+fn explain_redacted() {
+    setup();
+    let explain = |args: &[&str]| {
+        let mut output = vec![];
+        Pipeline::parse(args.iter().copied())
+            .unwrap()
+            .run(&b""[..], &mut output)
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    };
+    let args = [
+        "aes-ecb", "-K", "secret", "if", "-C", "[md5]", "-T", "[aes-cbc", "-K", "secret", "-IV",
+        "iv]", "aes-ecb", "-K", "[secret", "hex]",
+    ];
+
+    let redacted = explain(&[&["-x", "-k"], &args[..]].concat());
+    assert!(!redacted.contains("secret"), "{}", redacted);
+    assert!(!redacted.contains("736563726574"), "{}", redacted);
+    assert_eq!(
+        redacted.matches("-K <redacted>\n").count(),
+        3,
+        "{}",
+        redacted
+    );
+    assert!(redacted.contains("-IV \"iv\"\n"), "{}", redacted);
+
+    let shown = explain(&[&["-x"], &args[..]].concat());
+    assert!(shown.contains("-K \"secret\"\n"), "{}", shown);
+    assert!(
+        shown.contains("-K [ secret hex ] = \"736563726574\"\n"),
+        "{}",
+        shown
+    );
+}
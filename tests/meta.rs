@@ -0,0 +1,90 @@
+use codec_rust::{Error, Pipeline};
+
+fn run(args: &[&str], input: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    Pipeline::parse(args.iter().copied())?.run(input, &mut output)?;
+    Ok(output)
+}
+
+fn assert_output(args: &[&str], input: &[u8], expected: &[u8]) {
+    match run(args, input) {
+        Ok(output) => assert_eq!(output, expected, "args: {:?}", args),
+        Err(err) => panic!("args: {:?}: {}", args, err),
+    }
+}
+
+/// The error of the failing codec.
+fn codec_error(args: &[&str], input: &[u8]) -> String {
+    match run(args, input) {
+        Err(Error::Codec { source, .. }) => source.to_string(),
+        Err(err) => panic!("args: {:?}: expect a codec error, found {}", args, err),
+        Ok(output) => panic!("args: {:?}: expect a codec error, found {:?}", args, output),
+    }
+}
+
+#[test]
+fn if_condition_errors() {
+    let args = [
+        "if", "-C", "[hex", "-d]", "-T", "[const", "-C", "yes]", "-E", "[const", "-C", "no]",
+    ];
+    assert_output(&args, b"6869", b"yes");
+    // a failing condition takes the else chain, on the whole input
+    assert_output(&args, b"68zz", b"no");
+    assert_output(
+        &["if", "-C", "[hex", "-d]", "-T", "[hex", "-d]"],
+        b"zz",
+        b"zz",
+    );
+    assert_output(
+        &["if", "-C", "[hex", "-d]", "-o", "-E", "[append", "-A", "!]"],
+        b"zz",
+        b"zz!",
+    );
+
+    // errors of the branches are reported
+    assert_eq!(
+        codec_error(&["if", "-C", "[id]", "-T", "[hex", "-d]"], b"zz"),
+        "codec hex (#1 in chain): invalid hex character `z`"
+    );
+}
+
+#[test]
+fn verify_roundtrip_mismatches() {
+    assert_output(&["verify-roundtrip", "-C", "[hex", "-d]"], b"6a6b", b"jk");
+    assert_eq!(
+        codec_error(&["verify-roundtrip", "-C", "[hex", "-d]"], b"6a6B"),
+        "verify-roundtrip: mismatch at offset 3: expect 0x42, found 0x62"
+    );
+    assert_eq!(
+        codec_error(&["verify-roundtrip", "-C", "[hex", "-d]"], b"6a 6b"),
+        "verify-roundtrip: mismatch at offset 2: expect 0x20, found 0x36"
+    );
+    // the inverse is longer than the input
+    assert_eq!(
+        codec_error(&["verify-roundtrip", "-C", "[base32", "-d]"], b"NBUQ"),
+        "verify-roundtrip: mismatch at offset 4: expect end of data, found 0x3d"
+    );
+}
+
+#[test]
+fn map_records() {
+    // a trailing separator is kept, and not followed by an empty record
+    assert_output(&["map", "-C", "[hex]"], b"a\nb\n", b"61\n62\n");
+    assert_output(&["map", "-C", "[hex]"], b"a\nb", b"61\n62");
+    assert_output(&["map", "-C", "[hex]"], b"a\n\nb", b"61\n\n62");
+    assert_output(&["map", "-C", "[hex]"], b"", b"");
+
+    assert_output(&["map", "-z", "-C", "[hex]"], b"a\0b\0", b"61\x0062\x00");
+    assert_output(&["map", "-z", "-C", "[hex]"], b"a\0b", b"61\x0062");
+    assert_output(&["map", "-z", "-C", "[hex]"], b"a\nb", b"610a62");
+
+    assert_output(
+        &["map", "-S", "::", "-C", "[hex]"],
+        b"a:b::c::",
+        b"613a62::63::",
+    );
+    assert_eq!(
+        codec_error(&["map", "-C", "[hex", "-d]"], b"61\nzz"),
+        "map: record 2: codec hex (#1 in chain): invalid hex character `z`"
+    );
+}
@@ -0,0 +1,386 @@
+use codec_rust::{executor::parser, Error, ParseError, Pipeline};
+
+fn run(args: &[&str], input: &str) -> Result<String, Error> {
+    let mut output = Vec::new();
+    Pipeline::parse(args.iter().copied())?.run(input.as_bytes(), &mut output)?;
+    Ok(String::from_utf8(output).expect("output is not utf-8"))
+}
+
+fn assert_output(args: &[&str], input: &str, expected: &str) {
+    match run(args, input) {
+        Ok(output) => assert_eq!(output, expected, "args: {:?}", args),
+        Err(err) => panic!("args: {:?}: {}", args, err),
+    }
+}
+
+fn parse_error(args: &[&str]) -> String {
+    located_error(args).message().to_string()
+}
+
+fn located_error(args: &[&str]) -> ParseError {
+    match Pipeline::parse(args.iter().copied()) {
+        Err(Error::Parse(err)) => err,
+        Err(err) => panic!("expect a parse error, found {}", err),
+        Ok(pipeline) => panic!("expect a parse error, parsed {:?}", pipeline),
+    }
+}
+
+#[test]
+fn plain_chain() {
+    assert_output(&["base64"], "hello", "aGVsbG8=");
+    assert_output(&["base64", "-d", "hex"], "aGVsbG8=", "68656c6c6f");
+}
+
+#[test]
+fn sub_codecs() {
+    assert_output(&["append", "-A", "[", "x", "hex", "]"], "a", "a78");
+    assert_output(&["append", "-A", "[x", "hex]"], "a", "a78");
+    assert_output(&["append", "-A", "[x", "hex", "]", "hex"], "a", "613738");
+}
+
+#[test]
+fn nested_sub_codecs() {
+    let args = ["append", "-A", "[x", "append", "-A", "[y", "hex]]"];
+    assert_output(&args, "a", "ax79");
+
+    let args = [
+        "append", "-A", "[", "x", "append", "-A", "[", "y", "hex", "]", "]",
+    ];
+    assert_output(&args, "a", "ax79");
+}
+
+#[test]
+fn empty_values() {
+    assert_output(&["append", "-A", "[]"], "a", "a");
+    assert_output(&["append", "-A", "", "hex"], "a", "61");
+
+    let args = ["append", "-A", "[", "[]", "const", "-C", "z", "]"];
+    assert_output(&args, "a", "az");
+}
+
+#[test]
+fn escaped_brackets() {
+    assert_output(&["append", "-A", "\\]"], "a", "a]");
+    assert_output(&["append", "-A", "\\["], "a", "a[");
+    assert_output(&["append", "-A", "\\[1,2]"], "a", "a[1,2]");
+    assert_output(&["append", "-A", "\\\\[x]"], "a", "a\\[x]");
+    assert_output(&["append", "-A", "x\\]"], "a", "ax]");
+    assert_output(&["append", "-A", "[x\\]", "hex]"], "a", "a785d");
+}
+
+#[test]
+fn backslashes_elsewhere_are_kept() {
+    assert_output(&["append", "-A", "\\n"], "a", "a\\n");
+    assert_output(&["append", "-A", "x\\"], "a", "ax\\");
+    assert_output(&["append", "-A", "x\\y"], "a", "ax\\y");
+}
+
+#[test]
+fn whitespaces_do_not_escape() {
+    assert_output(&["append", "-A", "[1, 2]"], "a", "a1, 2");
+    assert_output(&["append", "-A", "\\[1, 2]"], "a", "a[1, 2]");
+    assert_output(
+        &["append", "-A", "[", "a [d\\]", "hex]"],
+        "a",
+        "a61205b645d",
+    );
+    assert_output(&["append", "-A", " \\]"], "a", "a ]");
+    assert_eq!(parse_error(&["append", "-A", " ]"]), "unexpected ]");
+}
+
+#[test]
+fn chain_options() {
+    assert_output(&["if", "-C", "[md5]", "-T", "[hex]"], "a", "61");
+    assert_output(
+        &["if", "-C", "[base64", "-d]", "-E", "[", "hex", "]"],
+        "a",
+        "61",
+    );
+
+    let args = ["map", "-S", ",", "-C", "[append", "-A", "\\]", "]"];
+    assert_output(&args, "a,b", "a],b]");
+
+    // the whole argument is escaped, the second `]` does not close the chain
+    let args = ["map", "-S", ",", "-C", "[append", "-A", "\\]]"];
+    assert_eq!(parse_error(&args), "expect ], found EOF");
+}
+
+#[test]
+fn unbalanced_brackets() {
+    assert_eq!(
+        parse_error(&["append", "-A", "[x", "hex"]),
+        "expect ], found EOF"
+    );
+    assert_eq!(parse_error(&["base64", "]"]), "unexpected ]");
+    assert_eq!(parse_error(&["append", "-A", "]"]), "unexpected ]");
+    assert_eq!(parse_error(&["if", "-C", "md5"]), "expect [, found md5");
+    assert_eq!(parse_error(&["if", "-C", "[md5"]), "expect ], found EOF");
+}
+
+#[test]
+fn invalid_options() {
+    assert_eq!(parse_error(&["-"]), "empty option name");
+    assert_eq!(parse_error(&["append", "-A"]), "expect a value, found EOF");
+}
+
+#[test]
+fn int_options_are_checked() {
+    let reason = |args: &[&str]| match run(args, "hello") {
+        Err(Error::InvalidOption { reason, .. }) => reason,
+        Err(err) => panic!("expect an invalid option, found {}", err),
+        Ok(output) => panic!("expect an invalid option, found output {:?}", output),
+    };
+    #[cfg(feature = "libc")]
+    assert_eq!(
+        reason(&["zlib", "-L", "12"]),
+        "expect int in [0, 9], found `12`"
+    );
+    assert_eq!(
+        reason(&["repeat", "-T", "-1"]),
+        "expect int >= 0, found `-1`"
+    );
+    assert_eq!(reason(&["take", "-B", "x"]), "expect int >= 0, found `x`");
+    assert_eq!(reason(&["take", "-B", "-1"]), "expect int >= 0, found `-1`");
+    assert_eq!(reason(&["drop", "-B", "-1"]), "expect int >= 0, found `-1`");
+    assert_output(&["repeat", "-T", "0"], "hello", "");
+}
+
+#[test]
+fn error_locations() {
+    let err = located_error(&["base64", "]", "hex"]);
+    assert_eq!((err.index(), err.token()), (Some(1), Some("]")));
+
+    let err = located_error(&["append", "-A", "[x", "hex]]"]);
+    assert_eq!((err.index(), err.token()), (Some(3), Some("]")));
+
+    let err = located_error(&["append", "-A", "[x", "hex"]);
+    assert_eq!((err.index(), err.token()), (Some(4), None));
+
+    let err = located_error(&["if", "-C", "[md5]]"]);
+    assert_eq!((err.index(), err.token()), (Some(2), Some("]")));
+
+    let err = located_error(&["-", "base64"]);
+    assert_eq!((err.index(), err.token()), (Some(0), Some("-")));
+}
+
+#[test]
+fn error_rendering() {
+    let err = located_error(&["base64", "]", "hex"]);
+    assert_eq!(
+        err.to_string(),
+        "unexpected ]\n    base64 ] hex\n           ^"
+    );
+
+    let err = located_error(&["append", "-A", "[x", "hex]]"]);
+    assert_eq!(
+        err.to_string(),
+        "unexpected ]\n    append -A [x hex]]\n                     ^"
+    );
+
+    let err = located_error(&["if", "-C", "md5"]);
+    assert_eq!(
+        err.to_string(),
+        "expect [, found md5\n    if -C md5\n          ^^^"
+    );
+
+    let err = located_error(&["append", "-A"]);
+    assert_eq!(
+        err.to_string(),
+        "expect a value, found EOF\n    append -A\n              ^"
+    );
+
+    let err = located_error(&["append", "-A", "a b", "]"]);
+    assert_eq!(
+        err.to_string(),
+        "unexpected ]\n    append -A 'a b' ]\n                    ^"
+    );
+}
+
+fn round_trip(text: &str) -> String {
+    let command = parser::parse_str(text).unwrap_or_else(|err| panic!("{}: {}", text, err));
+    let printed = command.to_string();
+    let reparsed = parser::parse_str(&printed).unwrap_or_else(|err| panic!("{}: {}", printed, err));
+    assert_eq!(command, reparsed, "printed as {}", printed);
+    printed
+}
+
+#[test]
+fn split_str() {
+    let split = |text| parser::split_str(text).unwrap();
+    assert_eq!(split("  base64\n\thex -c  "), ["base64", "hex", "-c"]);
+    assert_eq!(split("# comment\nbase64 # hex\nhex"), ["base64", "hex"]);
+    assert_eq!(split("append -A a#b"), ["append", "-A", "a#b"]);
+    assert_eq!(split("-A 'a b' -B \"c d\""), ["-A", "a b", "-B", "c d"]);
+    assert_eq!(split("-A '' x'y'z"), ["-A", "", "xyz"]);
+    assert_eq!(
+        split(r#"-A "a\"b\\c\d" 'e\f'"#),
+        ["-A", "a\"b\\c\\d", "e\\f"]
+    );
+    assert_eq!(split(r"-A \] a\ b \\[ \'"), ["-A", "]", "a b", "\\[", "'"]);
+    assert_eq!(split("base64 \\\n hex"), ["base64", "hex"]);
+    assert_eq!(split("-A 'a\nb'"), ["-A", "a\nb"]);
+}
+
+#[test]
+fn split_str_errors() {
+    let error = |text| parser::split_str(text).unwrap_err().to_string();
+    assert_eq!(error("-A 'a b"), "unterminated '");
+    assert_eq!(error("base64\n-A \"a\\\"\n"), "line 2: unterminated \"");
+    assert_eq!(error("-A a\\"), "unexpected end after \\");
+}
+
+#[test]
+fn parse_str_is_parse() {
+    let texts = [
+        "-d base64 zlib",
+        "append -A [x hex] base64",
+        "if -C [md5] -T [ hex ] # comment",
+        "append -A '[1, 2]' append -A '\\]'",
+    ];
+    for text in texts {
+        let args = parser::split_str(text).unwrap();
+        let mut tokenizer = parser::Tokenizer::new(args);
+        let command = parser::parse_command(&mut tokenizer).unwrap();
+        assert_eq!(parser::parse_str(text).unwrap(), command, "{}", text);
+    }
+
+    let mut output = vec![];
+    Pipeline::parse_str("append -A [x hex] # comment\nhex")
+        .unwrap()
+        .run(&b"a"[..], &mut output)
+        .unwrap();
+    assert_eq!(output, b"613738");
+}
+
+#[test]
+fn canonical_strings() {
+    assert_eq!(round_trip("-d   base64\nzlib"), "-d base64 zlib");
+    assert_eq!(round_trip("append -A [x hex]"), "append -A [ x hex ]");
+    assert_eq!(
+        round_trip("append -A [ [] const -C y ]"),
+        "append -A [ [] const -C y ]"
+    );
+    assert_eq!(round_trip("append -A []"), "append -A []");
+    assert_eq!(
+        round_trip("if -C [md5] -T [] -o"),
+        "if -C [ md5 ] -T [ ] -o"
+    );
+    assert_eq!(round_trip("append -A 'a b'"), "append -A 'a b'");
+    assert_eq!(round_trip("append -A \"it's\""), "append -A 'it'\\''s'");
+    assert_eq!(round_trip("append -A '\\]'"), "append -A '\\]'");
+    assert_eq!(round_trip("append -A 'x\\]'"), "append -A 'x\\]'");
+    assert_eq!(round_trip("append -A '\\[1,2]'"), "append -A '\\[1,2]'");
+    assert_eq!(round_trip("append -A '#'"), "append -A '#'");
+}
+
+#[test]
+fn canonical_strings_round_trip() {
+    let texts = [
+        "-n -I hello base64 -u",
+        "append -A [x append -A [y hex]] base64",
+        "map -S , -C [append -A '\\]' hex]",
+        "append -A [ 'a b\\]' hex ]",
+        "append -A '[1, 2]' append -A '\\[1, 2]'",
+        "append -A [ '' hex ]",
+        "append -A '\\\\[' append -A 'a\\'",
+        "if -C [zlib -d] -E [base64] -T [if -C [id] -T [hex]]",
+    ];
+    for text in texts {
+        round_trip(text);
+    }
+}
+
+#[test]
+fn long_options() {
+    assert_output(&["append", "--string", "x"], "a", "ax");
+    assert_output(&["hex", "--group", "1", "hex", "--decode"], "ab", "ab");
+    assert_output(
+        &["if", "--condition", "[md5]", "--then", "[hex]"],
+        "a",
+        "61",
+    );
+    assert_output(&["--input", "b", "--decode", "hex", "--encode"], "a", "62");
+    assert_output(&["base64", "--url", "--no-padding"], "?>", "Pz4");
+}
+
+#[test]
+fn inline_values() {
+    assert_output(&["append", "-A=x"], "a", "ax");
+    assert_output(&["append", "--string=x=y"], "a", "ax=y");
+    assert_output(&["append", "-A=", "hex"], "a", "61");
+    assert_output(&["append", "--string=", "hex"], "a", "61");
+    assert_output(&["append", "-A=[x"], "a", "a[x");
+    assert_output(&["-I=b", "-E=sequential", "hex"], "a", "62");
+}
+
+#[test]
+fn options_without_spec() {
+    // `x` is not a declared codec: capitalization decides
+    assert_eq!(round_trip("x -a -B b -c=c -- y"), "x -a -B b -c=c y");
+    assert_eq!(round_trip("x -c= hex"), "x -c= hex");
+    assert_eq!(round_trip("x '-c=a b' -D=d"), "x '-c=a b' -D d");
+
+    let err = located_error(&["x", "--long"]);
+    assert_eq!(err.message(), "unknown option --long");
+}
+
+#[test]
+fn option_kinds_come_from_specs() {
+    // `-8` is a switch of rsa-crypt without being lower case
+    #[cfg(feature = "libc")]
+    {
+        let command = parser::parse_str("rsa-crypt -8 -dr -PS pkcs15 hex").unwrap();
+        assert_eq!(command.to_string(), "rsa-crypt -8 -dr -PS pkcs15 hex");
+    }
+
+    assert_eq!(parse_error(&["base64", "-u=1"]), "option -u takes no value");
+    assert_eq!(
+        parse_error(&["if", "--condition=md5"]),
+        "option -C expects a chain [codecs]"
+    );
+    assert_eq!(
+        parse_error(&["append", "--strin=x"]),
+        "unknown option --strin"
+    );
+}
+
+#[test]
+fn end_of_options() {
+    assert_output(&["-n", "--", "hex"], "a", "61\n");
+    assert_output(&["hex", "-c", "--", "append", "-A", "x"], "\u{ff}", "C3BFx");
+
+    let command = parser::parse_str("base64 -u -- hex --").unwrap();
+    assert_eq!(command.to_string(), "base64 -u hex");
+}
+
+#[test]
+fn scripts() {
+    let path = std::env::temp_dir().join(format!("codec-script-{}", std::process::id()));
+    std::fs::write(&path, "# encode\nhex \\\n  append -A ' !'\n").unwrap();
+    let script = path.to_str().unwrap();
+
+    assert_output(&["-f", script], "hi", "6869 !");
+    assert_output(&["--script", script, "base64"], "hi", "Njg2OSAh");
+    assert_output(&["-n", &format!("-f={}", script)], "hi", "6869 !\n");
+    // the value of another option is not a script
+    assert_output(&["-I", "-f", "hex"], "", "2d66");
+    // nor is an option of the codecs
+    Pipeline::parse(["usage", "-f", "json"]).unwrap();
+
+    assert_eq!(parse_error(&["-f"]), "-f: expect a script file");
+    assert_eq!(
+        parse_error(&["-f", script, "-f"]),
+        "-f: expect a script file"
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert!(parse_error(&["-f", script]).starts_with(script));
+    match Pipeline::parse_str("-f script hex") {
+        Err(Error::Parse(err)) => {
+            assert_eq!(
+                err.message(),
+                "-f: scripts are only read from the command line"
+            )
+        }
+        result => panic!("expect a parse error, found {:?}", result),
+    }
+}
@@ -0,0 +1,119 @@
+#![cfg(unix)]
+
+use std::{os::unix::fs::PermissionsExt, path::PathBuf, sync::Once};
+
+use codec_rust::{codecs::plugin, load_builtins, CodecMetaInfo, Error, Pipeline};
+
+/// Prints its arguments and environment on a line, then copies its input.
+const ARGS_PLUGIN: &str = r#"#!/bin/sh
+if [ "$1" = --codec-usage ]; then
+    echo "    args: print the arguments, then the input"
+    exit 0
+fi
+printf '%s|' "$@"
+echo "$CODEC_MODE $CODEC_PLUGIN_NAME"
+exec cat
+"#;
+
+/// Reads its input and fails.
+const FAIL_PLUGIN: &str = "#!/bin/sh\ncat > /dev/null\nexit 3\n";
+
+fn write_plugin(dir: &std::path::Path, name: &str, script: &str) {
+    let path = dir.join(format!("codec-{}", name));
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Registers the plugins of the tests, found through `$CODEC_PLUGIN_PATH`.
+fn setup() {
+    static SETUP: Once = Once::new();
+    SETUP.call_once(|| {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("codec-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_plugin(&dir, "args", ARGS_PLUGIN);
+        write_plugin(&dir, "fail", FAIL_PLUGIN);
+
+        std::env::set_var("CODEC_PLUGIN_PATH", &dir);
+        let mut codecs = load_builtins();
+        plugin::load_plugins(&mut codecs, &plugin::default_plugin_dirs());
+        CodecMetaInfo::set_instance(codecs);
+    });
+}
+
+fn run(args: &[&str], input: &[u8]) -> Result<Vec<u8>, Error> {
+    setup();
+    let mut output = Vec::new();
+    Pipeline::parse(args.iter().copied())?.run(input, &mut output)?;
+    Ok(output)
+}
+
+fn run_str(args: &[&str], input: &str) -> String {
+    let output = run(args, input.as_bytes()).unwrap_or_else(|err| panic!("{:?}: {}", args, err));
+    String::from_utf8(output).expect("output is not utf-8")
+}
+
+#[test]
+fn arguments() {
+    assert_eq!(run_str(&["args"], "in"), "encode|encode args\nin");
+    assert_eq!(
+        run_str(&["args", "-k", "-B", "x y", "-a=1"], "in"),
+        "encode|-B|x y|-a|1|-k|encode args\nin"
+    );
+    // the mode switches only choose the mode
+    assert_eq!(run_str(&["args", "-d"], "in"), "decode|decode args\nin");
+    assert_eq!(
+        run_str(&["-d", "args", "-e"], "in"),
+        "encode|encode args\nin"
+    );
+
+    match run(&["args", "-e", "-d"], b"in") {
+        Err(Error::Codec { source, .. }) => assert_eq!(
+            source.to_string(),
+            "args: -e and -d cannot be used together"
+        ),
+        result => panic!("expect a codec error, found {:?}", result),
+    }
+}
+
+#[test]
+fn streaming() {
+    // larger than the pipe buffers, stdin is written while stdout is read
+    let input: Vec<u8> = (0..=255u8).cycle().take(1 << 20).collect();
+    let mut expected = b"encode|encode args\n".to_vec();
+    expected.extend(&input);
+    assert!(run(&["args"], &input).unwrap() == expected);
+
+    // the plugin is stopped once the rest of the chain stops reading
+    let output = run(&["args", "take", "-B", "23"], &input).unwrap();
+    assert_eq!(output, &expected[..23]);
+}
+
+#[test]
+fn failure() {
+    match run(&["hex", "fail", "hex"], b"in") {
+        Err(Error::Codec {
+            name,
+            index,
+            source,
+        }) => {
+            assert_eq!((name.as_str(), index), ("fail", 1));
+            assert_eq!(
+                source.to_string(),
+                "fail: plugin exited with exit status: 3"
+            );
+        }
+        result => panic!("expect a codec error, found {:?}", result),
+    }
+}
+
+#[test]
+fn usage() {
+    let usage = run_str(&["-H", "args"], "");
+    assert!(
+        usage.contains("    args: print the arguments, then the input\n"),
+        "{}",
+        usage
+    );
+    assert!(usage.contains("codec-args)\n"), "{}", usage);
+}
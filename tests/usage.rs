@@ -0,0 +1,123 @@
+use std::{
+    io::{Read, Write},
+    sync::Once,
+};
+
+use codec_rust::{
+    codecs::{Example, OptionSpec},
+    load_builtins, Codec, CodecMetaInfo, CodecMode, CodecUsage, Options, Pipeline,
+};
+
+/// Copies its input, with a usage full of roff control characters.
+struct Dots;
+
+impl Codec for Dots {
+    fn run_codec(
+        &self,
+        input: &mut (dyn Read + Send),
+        _global_mode: CodecMode,
+        _options: &Options,
+        output: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        std::io::copy(input, output)?;
+        Ok(())
+    }
+
+    fn as_codec_usage(&self) -> Option<&dyn CodecUsage> {
+        Some(self)
+    }
+
+    fn options(&self) -> Option<Vec<OptionSpec>> {
+        Some(vec![OptionSpec::switch("x", ".so is not a request")])
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example::new("dots", "in", ".in")]
+    }
+}
+
+impl CodecUsage for Dots {
+    fn usage(&self) -> String {
+        "    dots: copy the input
+    .TH is not a request
+    'nor is this
+    -x is a dash
+"
+        .to_string()
+    }
+}
+
+fn setup() {
+    static SETUP: Once = Once::new();
+    SETUP.call_once(|| {
+        let mut codecs = load_builtins();
+        codecs.register("dots", Box::new(Dots));
+        CodecMetaInfo::set_instance(codecs);
+    });
+}
+
+fn usage(args: &[&str]) -> String {
+    setup();
+    let mut output = vec![];
+    Pipeline::parse([&["usage"], args].concat())
+        .unwrap()
+        .run(&b""[..], &mut output)
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn json() {
+    let usage = usage(&["-f", "json"]);
+    let usage: serde_json::Value = serde_json::from_str(&usage)
+        .unwrap_or_else(|err| panic!("invalid JSON {:?}: {}", usage, err));
+    let codecs = usage["codecs"].as_array().unwrap();
+    assert_eq!(
+        codecs.len(),
+        CodecMetaInfo::instance().codecs_iter().count()
+    );
+
+    let codec = |name: &str| codecs.iter().find(|codec| codec["name"] == name).unwrap();
+    let take = codec("take");
+    assert_eq!(take["modes"], serde_json::json!(["encode", "decode"]));
+    assert_eq!(take["invertible"], false);
+    let count = &take["options"][0];
+    assert_eq!(count["long"], "bytes");
+    assert_eq!(count["value_type"], "int");
+    assert_eq!(count["min"], 0);
+    assert!(count["max"].is_null());
+    assert_eq!(count["default"], "0");
+
+    let dots = codec("dots");
+    assert_eq!(
+        dots["description"],
+        "dots: copy the input\n.TH is not a request\n'nor is this\n-x is a dash"
+    );
+    assert_eq!(dots["options"][0]["kind"], "switch");
+    assert_eq!(dots["examples"][0]["output"], ".in");
+}
+
+#[test]
+fn man_escapes_control_characters() {
+    let man = usage(&["-f", "man", "-C", "dots"]);
+    let lines: Vec<&str> = man.lines().collect();
+    for line in [
+        "\\&.TH is not a request",
+        "\\&'nor is this",
+        "\\-x is a dash",
+        "\\&.so is not a request",
+        "\\&.in",
+    ] {
+        assert!(lines.contains(&line), "{:?} in\n{}", line, man);
+    }
+    // only the requests of the page start with `.`
+    for line in lines.iter().filter(|line| line.starts_with('.')) {
+        let request = line.split(' ').next().unwrap();
+        assert!(
+            [".TH", ".SH", ".SS", ".B", ".nf", ".fi", ".PP", ".TP", ".RS", ".RE"]
+                .contains(&request),
+            "{}",
+            line
+        );
+    }
+}